@@ -1,10 +1,12 @@
-use graphics::math::Matrix2d;
-
 ///Todo change Direction and [f64;2] to Matrix2d
 pub trait Animator {
     fn animate(&mut self, direction: Direction) -> [f64; 2];
     fn is_over(&self) -> bool;
     fn start(&mut self);
+    ///Finish current animation at once
+    fn stop(&mut self);
+    ///Shift of finished animation, the view scales it to the cell size
+    fn distance(&self) -> f64;
 }
 
 pub struct PlainAnimator {
@@ -54,6 +56,14 @@ impl Animator for PlainAnimator {
     fn start(&mut self) {
         self.reset();
     }
+
+    fn stop(&mut self) {
+        self.count = self.max;
+    }
+
+    fn distance(&self) -> f64 {
+        self.max
+    }
 }
 
 #[derive(Debug, Ord, PartialOrd, Eq, PartialEq, Hash, Copy, Clone)]
@@ -63,3 +73,16 @@ pub enum Direction {
     Bottom,
     Left,
 }
+
+impl Direction {
+    pub const ALL: [Direction; 4] = [Direction::Top, Direction::Right, Direction::Bottom, Direction::Left];
+
+    pub fn opposite(self) -> Direction {
+        match self {
+            Direction::Top => Direction::Bottom,
+            Direction::Right => Direction::Left,
+            Direction::Bottom => Direction::Top,
+            Direction::Left => Direction::Right,
+        }
+    }
+}
@@ -0,0 +1,504 @@
+//! Actions of the game and keys, mouse or gamepad buttons they are bound to.
+//! Gamepad buttons are numbered as SDL game controller does:
+//! A 0, B 1, X 2, Y 3, Back 4, Guide 5, Start 6, shoulders 9 and 10, D-pad up 11, down 12, left 13, right 14.
+//! Axes 0 and 1 are the left stick, positive values are right and down.
+//!
+//! Bindings are read from JSON file, every action in it gets only the listed buttons,
+//! other actions keep default ones:
+//! ```json
+//! {"undo": ["Backspace", "U", "Gamepad:1"], "select": ["Mouse:Left"], "quit": ["Q"]}
+//! ```
+//! Keys are named as piston `Key` is.
+
+use std::collections::{BTreeMap, HashMap};
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use piston::input::{Button, ControllerAxisArgs, ControllerButton, ControllerHat, GenericEvent, HatState, Key, MouseButton};
+
+use crate::animator::Direction;
+use self::Binding::Key as K;
+use crate::gameboard_controller::GameState;
+
+///Stick is pushed when it goes farther from the center
+const STICK_PUSHED: f64 = 0.6;
+///Pushed stick is released when it comes back closer to the center
+const STICK_RELEASED: f64 = 0.3;
+
+#[derive(Debug, Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Hash)]
+pub enum Action {
+    ///Begin the game on prepare screen
+    Start,
+    ///Slide the tile next to zero field in this direction
+    Move(Direction),
+    ///Take back the last move
+    Undo,
+    ///Do the move taken back again
+    Redo,
+    ///Select the tile of the next move of a shortest solution
+    Hint,
+    ///Stop the clock and hide the board, or go on
+    Pause,
+    ///Start the same board again
+    Restart,
+    ///Show or hide placed tiles and progress
+    Overlay,
+    ///Select the tile under cursor, or slide selected one to zero field
+    Select,
+    Deselect,
+    ///Next color theme, on any screen
+    Theme,
+    ///Close the window, on any screen
+    Quit,
+}
+
+///Screens where action works
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+enum Scope {
+    Prepare,
+    Game,
+    ///Game over, replay, level select and statistics screens, only fixed keys work there
+    Menus,
+    Everywhere,
+}
+
+///Buttons handled by the screens themselves, they can't be bound to actions working there
+const FIXED: [(Binding, Scope); 31] = [
+    (K(Key::G), Scope::Prepare), (K(Key::S), Scope::Prepare), (K(Key::L), Scope::Prepare),
+    (K(Key::P), Scope::Prepare), (K(Key::F1), Scope::Prepare),
+    (K(Key::F5), Scope::Game), (K(Key::F1), Scope::Game),
+    //Zoom of the board
+    (K(Key::Equals), Scope::Game), (K(Key::NumPadPlus), Scope::Game),
+    (K(Key::Minus), Scope::Game), (K(Key::NumPadMinus), Scope::Game),
+    (K(Key::Equals), Scope::Menus), (K(Key::NumPadPlus), Scope::Menus),
+    (K(Key::Minus), Scope::Menus), (K(Key::NumPadMinus), Scope::Menus),
+    (K(Key::S), Scope::Menus), (K(Key::L), Scope::Menus), (K(Key::P), Scope::Menus),
+    (K(Key::F12), Scope::Menus), (K(Key::R), Scope::Menus), (K(Key::Return), Scope::Menus),
+    //Pan of zoomed board
+    (Binding::Mouse(MouseButton::Middle), Scope::Game), (Binding::Mouse(MouseButton::Middle), Scope::Menus),
+    //Replay playback and level list
+    (K(Key::Space), Scope::Menus), (K(Key::Up), Scope::Menus), (K(Key::Down), Scope::Menus),
+    (K(Key::Left), Scope::Menus), (K(Key::Right), Scope::Menus), (K(Key::Home), Scope::Menus),
+    (K(Key::End), Scope::Menus), (K(Key::Backspace), Scope::Menus),
+];
+
+impl Action {
+    pub const ALL: [Action; 15] = [
+        Action::Start,
+        Action::Move(Direction::Top), Action::Move(Direction::Right),
+        Action::Move(Direction::Bottom), Action::Move(Direction::Left),
+        Action::Undo, Action::Redo, Action::Hint, Action::Pause, Action::Restart, Action::Overlay,
+        Action::Select, Action::Deselect, Action::Theme, Action::Quit,
+    ];
+
+    fn scope(self) -> Scope {
+        match self {
+            Action::Start => Scope::Prepare,
+            Action::Theme | Action::Quit => Scope::Everywhere,
+            _ => Scope::Game
+        }
+    }
+
+    ///Action works on `state` screen
+    pub fn is_active(self, state: GameState) -> bool {
+        match self.scope() {
+            Scope::Prepare => state == GameState::Prepare,
+            Scope::Game => state == GameState::InProcess,
+            Scope::Menus => false,
+            Scope::Everywhere => true,
+        }
+    }
+
+    ///Both actions may work on the same screen, so they can't share a button
+    fn overlaps(self, other: Action) -> bool {
+        self.works_with(other.scope())
+    }
+
+    ///Action may work on the same screen as the keys of `scope`
+    fn works_with(self, scope: Scope) -> bool {
+        let action = self.scope();
+        action == scope || action == Scope::Everywhere || scope == Scope::Everywhere
+    }
+}
+
+impl fmt::Display for Action {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Action::Start => "start",
+            Action::Move(Direction::Top) => "move_up",
+            Action::Move(Direction::Right) => "move_right",
+            Action::Move(Direction::Bottom) => "move_down",
+            Action::Move(Direction::Left) => "move_left",
+            Action::Undo => "undo",
+            Action::Redo => "redo",
+            Action::Hint => "hint",
+            Action::Pause => "pause",
+            Action::Restart => "restart",
+            Action::Overlay => "overlay",
+            Action::Select => "select",
+            Action::Deselect => "deselect",
+            Action::Theme => "theme",
+            Action::Quit => "quit",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl FromStr for Action {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Action::ALL.iter()
+            .find(|a| a.to_string() == s)
+            .copied()
+            .ok_or_else(|| format!("unknown action '{}'", s))
+    }
+}
+
+///Key, mouse button or button of any gamepad
+#[derive(Debug, Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Hash)]
+pub enum Binding {
+    Key(Key),
+    Mouse(MouseButton),
+    Gamepad(u8),
+}
+
+impl Binding {
+    ///Binding of pressed or released `button`, D-pad hats have fixed moves
+    pub fn of(button: Button) -> Option<Binding> {
+        match button {
+            Button::Keyboard(key) => Some(Binding::Key(key)),
+            Button::Mouse(mouse) => Some(Binding::Mouse(mouse)),
+            Button::Controller(ControllerButton { button, .. }) => Some(Binding::Gamepad(button)),
+            _ => None
+        }
+    }
+}
+
+impl fmt::Display for Binding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        //Names of piston enums are the ones serde gives
+        let name = |value: serde_json::Result<String>| value.unwrap_or_default().trim_matches('"').to_owned();
+        match self {
+            Binding::Key(key) => write!(f, "{}", name(serde_json::to_string(key))),
+            Binding::Mouse(mouse) => write!(f, "Mouse:{}", name(serde_json::to_string(mouse))),
+            Binding::Gamepad(button) => write!(f, "Gamepad:{}", button),
+        }
+    }
+}
+
+impl FromStr for Binding {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let unknown = |_| format!("unknown button '{}'", s);
+        let value = |name: &str| serde_json::Value::String(name.to_owned());
+        if let Some(mouse) = s.strip_prefix("Mouse:") {
+            serde_json::from_value(value(mouse)).map(Binding::Mouse).map_err(unknown)
+        } else if let Some(button) = s.strip_prefix("Gamepad:") {
+            button.parse().map(Binding::Gamepad).map_err(|_| format!("unknown button '{}'", s))
+        } else {
+            match serde_json::from_value(value(s)).map_err(unknown)? {
+                Key::Unknown => Err(format!("unknown button '{}'", s)),
+                key => Ok(Binding::Key(key))
+            }
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum BindingsError {
+    Io(io::Error),
+    Json(serde_json::Error),
+    ///Wrong action or button name
+    Name(String),
+    ///Button is bound to two actions working on the same screen
+    Conflict { binding: Binding, first: Action, second: Action },
+    ///Button is a fixed key of the screen where action works
+    Fixed { binding: Binding, action: Action },
+}
+
+impl fmt::Display for BindingsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BindingsError::Io(e) => write!(f, "bindings io error: {}", e),
+            BindingsError::Json(e) => write!(f, "bindings format error: {}", e),
+            BindingsError::Name(e) => write!(f, "bindings error: {}", e),
+            BindingsError::Conflict { binding, first, second } => {
+                write!(f, "{} is bound to both {} and {}", binding, first, second)
+            }
+            BindingsError::Fixed { binding, action } => {
+                write!(f, "{} is a fixed key and can't be bound to {}", binding, action)
+            }
+        }
+    }
+}
+
+impl Error for BindingsError {}
+
+impl From<io::Error> for BindingsError {
+    fn from(e: io::Error) -> Self {
+        BindingsError::Io(e)
+    }
+}
+
+impl From<serde_json::Error> for BindingsError {
+    fn from(e: serde_json::Error) -> Self {
+        BindingsError::Json(e)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Bindings {
+    ///Buttons of every action
+    pub actions: HashMap<Action, Vec<Binding>>,
+    ///Axes of the stick moving tiles left-right and up-down, None if stick isn't used
+    pub stick: Option<[u8; 2]>,
+}
+
+impl Default for Bindings {
+    fn default() -> Self {
+        use self::Action::*;
+        use self::Binding::{Gamepad, Key as K, Mouse};
+        use crate::animator::Direction::*;
+        let actions = vec![
+            (Start, vec![K(Key::Space), Gamepad(0), Gamepad(6)]),
+            (Move(Top), vec![K(Key::Up), Gamepad(11)]),
+            (Move(Right), vec![K(Key::Right), Gamepad(14)]),
+            (Move(Bottom), vec![K(Key::Down), Gamepad(12)]),
+            (Move(Left), vec![K(Key::Left), Gamepad(13)]),
+            (Undo, vec![K(Key::Backspace), Gamepad(1)]),
+            (Redo, vec![K(Key::Y), Gamepad(10)]),
+            (Hint, vec![K(Key::H), Gamepad(2)]),
+            (Pause, vec![K(Key::Space), Gamepad(6)]),
+            (Restart, vec![K(Key::R), Gamepad(3)]),
+            (Overlay, vec![K(Key::O), Gamepad(4)]),
+            (Select, vec![Mouse(MouseButton::Left)]),
+            (Deselect, vec![Mouse(MouseButton::Right)]),
+            (Theme, vec![K(Key::T)]),
+            (Quit, vec![K(Key::Escape)]),
+        ];
+        Bindings { actions: actions.into_iter().collect(), stick: Some([0, 1]) }
+    }
+}
+
+impl Bindings {
+    ///`bindings.json` in user config directory
+    pub fn default_path() -> Option<PathBuf> {
+        dirs::config_dir().map(|dir| dir.join("fifteen").join("bindings.json"))
+    }
+
+    ///Defaults changed by the file at `path`, no file means defaults
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, BindingsError> {
+        match fs::read_to_string(path) {
+            Ok(text) => Bindings::from_json(&text),
+            Err(ref e) if e.kind() == io::ErrorKind::NotFound => Ok(Bindings::default()),
+            Err(e) => Err(e.into())
+        }
+    }
+
+    ///Defaults changed by JSON object of action names and button lists
+    pub fn from_json(text: &str) -> Result<Self, BindingsError> {
+        let file: BTreeMap<String, Vec<String>> = serde_json::from_str(text)?;
+        let mut bindings = Bindings::default();
+        for (action, buttons) in file {
+            let action: Action = action.parse().map_err(BindingsError::Name)?;
+            let buttons = buttons.iter()
+                .map(|b| b.parse())
+                .collect::<Result<Vec<Binding>, String>>()
+                .map_err(BindingsError::Name)?;
+            bindings.actions.insert(action, buttons);
+        }
+        if let Some(&(binding, first, second)) = bindings.conflicts().first() {
+            return Err(BindingsError::Conflict { binding, first, second });
+        }
+        match bindings.fixed().first() {
+            Some(&(binding, action)) => Err(BindingsError::Fixed { binding, action }),
+            None => Ok(bindings)
+        }
+    }
+
+    ///Buttons of actions which are fixed keys on the same screen, the screen would take them
+    pub fn fixed(&self) -> Vec<(Binding, Action)> {
+        let mut fixed = vec![];
+        for action in Action::ALL.iter() {
+            for binding in self.bound(*action) {
+                let taken = FIXED.iter().any(|&(fixed, scope)| *binding == fixed && action.works_with(scope));
+                if taken && !fixed.contains(&(*binding, *action)) {
+                    fixed.push((*binding, *action));
+                }
+            }
+        }
+        fixed
+    }
+
+    ///Buttons bound to two actions which work on the same screen
+    pub fn conflicts(&self) -> Vec<(Binding, Action, Action)> {
+        let mut conflicts = vec![];
+        for (i, first) in Action::ALL.iter().enumerate() {
+            for second in Action::ALL[i + 1..].iter().filter(|a| a.overlaps(*first)) {
+                for binding in self.bound(*first) {
+                    if self.bound(*second).contains(binding) && !conflicts.contains(&(*binding, *first, *second)) {
+                        conflicts.push((*binding, *first, *second));
+                    }
+                }
+            }
+        }
+        conflicts
+    }
+
+    ///Buttons of `action`
+    pub fn bound(&self, action: Action) -> &[Binding] {
+        self.actions.get(&action).map_or(&[], |b| b.as_slice())
+    }
+
+    ///Button of `action` shown in hints, keys go before mouse and gamepad
+    pub fn button_name(&self, action: Action) -> Option<String> {
+        let bound = self.bound(action);
+        bound.iter().find(|b| matches!(b, Binding::Key(_))).or_else(|| bound.first()).map(|b| b.to_string())
+    }
+
+    ///`button` is bound to `action`
+    pub fn is_bound(&self, action: Action, button: Button) -> bool {
+        Binding::of(button).is_some_and(|b| self.bound(action).contains(&b))
+    }
+
+    ///Action of pressed button working on `state` screen. D-pad hat always moves tiles
+    pub fn action<E: GenericEvent>(&self, event: &E, state: GameState) -> Option<Action> {
+        let button = event.press_args()?;
+        if let Button::Hat(ControllerHat { state: hat, .. }) = button {
+            return hat_direction(hat).map(Action::Move).filter(|a| a.is_active(state));
+        }
+        Action::ALL.iter().copied().find(|a| a.is_active(state) && self.is_bound(*a, button))
+    }
+
+    ///Move by the stick, once for every push. `held` keeps direction of the push until the stick is back
+    pub fn stick_action(&self, args: ControllerAxisArgs, held: &mut Option<Direction>) -> Option<Action> {
+        let [horizontal, vertical] = self.stick?;
+        let (back, forward) = if args.axis == horizontal {
+            (Direction::Left, Direction::Right)
+        } else if args.axis == vertical {
+            (Direction::Top, Direction::Bottom)
+        } else {
+            return None;
+        };
+        if args.position.abs() < STICK_RELEASED && (*held == Some(back) || *held == Some(forward)) {
+            *held = None;
+        }
+        if args.position.abs() > STICK_PUSHED && held.is_none() {
+            let direction = if args.position > 0.0 { forward } else { back };
+            *held = Some(direction);
+            return Some(Action::Move(direction));
+        }
+        None
+    }
+}
+
+///Direction of D-pad hat, diagonals aren't moves
+fn hat_direction(state: HatState) -> Option<Direction> {
+    match state {
+        HatState::Up => Some(Direction::Top),
+        HatState::Right => Some(Direction::Right),
+        HatState::Down => Some(Direction::Bottom),
+        HatState::Left => Some(Direction::Left),
+        _ => None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use piston::input::{ButtonArgs, ButtonState, Event, Input};
+
+    fn press(button: Button) -> Event {
+        Event::Input(Input::Button(ButtonArgs { state: ButtonState::Press, button, scancode: None }), None)
+    }
+
+    #[test]
+    fn action_test() {
+        let bindings = Bindings::default();
+        let game = GameState::InProcess;
+        assert_eq!(bindings.action(&press(Button::Controller(ControllerButton::new(0, 12))), game), Some(Action::Move(Direction::Bottom)));
+        assert_eq!(bindings.action(&press(Button::Controller(ControllerButton::new(1, 1))), game), Some(Action::Undo));
+        assert_eq!(bindings.action(&press(Button::Controller(ControllerButton::new(0, 0))), game), None);
+        assert_eq!(bindings.action(&press(Button::Hat(ControllerHat::new(0, 0, HatState::Left))), game), Some(Action::Move(Direction::Left)));
+        assert_eq!(bindings.action(&press(Button::Hat(ControllerHat::new(0, 0, HatState::LeftUp))), game), None);
+        assert_eq!(bindings.action(&press(Button::Keyboard(Key::H)), game), Some(Action::Hint));
+        //Same key works by screen
+        assert_eq!(bindings.action(&press(Button::Keyboard(Key::Space)), game), Some(Action::Pause));
+        assert_eq!(bindings.action(&press(Button::Keyboard(Key::Space)), GameState::Prepare), Some(Action::Start));
+        assert_eq!(bindings.action(&press(Button::Keyboard(Key::T)), GameState::Stats), Some(Action::Theme));
+        assert_eq!(bindings.action(&press(Button::Keyboard(Key::H)), GameState::Prepare), None);
+        let release = Event::Input(Input::Button(ButtonArgs {
+            state: ButtonState::Release, button: Button::Keyboard(Key::H), scancode: None,
+        }), None);
+        assert_eq!(bindings.action(&release, game), None);
+    }
+
+    #[test]
+    fn stick_test() {
+        let bindings = Bindings::default();
+        let mut held = None;
+        let mut stick = |axis, position| bindings.stick_action(ControllerAxisArgs::new(0, axis, position), &mut held);
+        assert_eq!(stick(1, -0.4), None);
+        assert_eq!(stick(1, -0.9), Some(Action::Move(Direction::Top)));
+        //Still pushed, and other axis is ignored until it is back
+        assert_eq!(stick(1, -1.0), None);
+        assert_eq!(stick(0, 0.9), None);
+        assert_eq!(stick(1, -0.1), None);
+        assert_eq!(stick(0, 0.9), Some(Action::Move(Direction::Right)));
+        assert_eq!(stick(3, 1.0), None);
+    }
+
+    #[test]
+    fn load_test() {
+        assert!(Bindings::default().conflicts().is_empty());
+        assert!(Bindings::default().fixed().is_empty());
+        for action in Action::ALL.iter() {
+            assert_eq!(action.to_string().parse::<Action>(), Ok(*action));
+            assert!(!Bindings::default().bound(*action).is_empty());
+        }
+        for name in &["Space", "D1", "Mouse:Right", "Gamepad:11"] {
+            assert_eq!(name.parse::<Binding>().unwrap().to_string(), *name);
+        }
+        assert!("Mouse:Nose".parse::<Binding>().is_err());
+        assert!("Spacebar".parse::<Binding>().is_err());
+
+        let bindings = Bindings::from_json(r#"{"undo": ["U", "Gamepad:9"], "quit": ["Q"], "select": ["Mouse:Left", "Return"]}"#).unwrap();
+        assert_eq!(bindings.bound(Action::Undo), &[Binding::Key(Key::U), Binding::Gamepad(9)]);
+        assert_eq!(bindings.bound(Action::Hint), &[Binding::Key(Key::H), Binding::Gamepad(2)]);
+        assert!(bindings.is_bound(Action::Select, Button::Keyboard(Key::Return)));
+
+        match Bindings::from_json(r#"{"hint": ["Backspace"]}"#) {
+            Err(BindingsError::Conflict { binding, first, second }) => {
+                assert_eq!((binding, first, second), (Binding::Key(Key::Backspace), Action::Undo, Action::Hint));
+            }
+            other => panic!("{:?}", other),
+        }
+        //Theme works everywhere, so it can't share a key with start
+        assert!(Bindings::from_json(r#"{"theme": ["Space"]}"#).is_err());
+        //Keys of the screens themselves
+        match Bindings::from_json(r#"{"start": ["G"]}"#) {
+            Err(BindingsError::Fixed { binding, action }) => assert_eq!((binding, action), (Binding::Key(Key::G), Action::Start)),
+            other => panic!("{:?}", other),
+        }
+        assert!(matches!(Bindings::from_json(r#"{"hint": ["F5"]}"#), Err(BindingsError::Fixed { .. })));
+        assert!(matches!(Bindings::from_json(r#"{"undo": ["Minus"]}"#), Err(BindingsError::Fixed { .. })));
+        assert!(matches!(Bindings::from_json(r#"{"theme": ["F12"]}"#), Err(BindingsError::Fixed { .. })));
+        //Fixed keys of other screens are free
+        assert!(Bindings::from_json(r#"{"hint": ["G"], "start": ["F5", "Return"], "undo": ["F12"]}"#).is_ok());
+        assert!(matches!(Bindings::from_json(r#"{"deselect": ["Mouse:Middle"]}"#), Err(BindingsError::Fixed { .. })));
+        //Hints name keys first
+        assert_eq!(Bindings::default().button_name(Action::Start), Some("Space".to_owned()));
+        let bindings = Bindings::from_json(r#"{"start": ["Gamepad:0", "Return"], "pause": ["Gamepad:6"], "quit": []}"#).unwrap();
+        assert_eq!(bindings.button_name(Action::Start), Some("Return".to_owned()));
+        assert_eq!(bindings.button_name(Action::Pause), Some("Gamepad:6".to_owned()));
+        assert_eq!(bindings.button_name(Action::Quit), None);
+        assert!(matches!(Bindings::from_json(r#"{"fly": ["F"]}"#), Err(BindingsError::Name(_))));
+        assert!(matches!(Bindings::from_json("[1]"), Err(BindingsError::Json(_))));
+    }
+}
@@ -0,0 +1,273 @@
+//! Command line arguments of the game binary.
+
+use std::ops::RangeInclusive;
+use std::path::PathBuf;
+
+use clap::{Parser, Subcommand};
+use piston_fifteen::daily;
+use piston_fifteen::difficulty::Difficulty;
+use piston_fifteen::gameboard::{BoardFormat, DEFAULT_SIZE, MAX_SIZE, MIN_SIZE};
+use piston_fifteen::gameboard_view::Theme;
+use piston_fifteen::goal::GoalLayout;
+use piston_fifteen::topology::Topology;
+
+#[derive(Debug, Parser)]
+#[command(name = "fifteen", version, about = "Fifteen puzzle")]
+pub struct Args {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
+    ///Width and height of the board
+    #[arg(long, default_value_t = DEFAULT_SIZE, value_parser = parse_size)]
+    pub size: usize,
+
+    ///Seed of the board, random if not set
+    #[arg(long)]
+    pub seed: Option<u64>,
+
+    ///Colors: light or dark
+    #[arg(long, default_value_t)]
+    pub theme: Theme,
+
+    ///TrueType font of the window
+    #[arg(long, default_value = "assets/amazone.ttf")]
+    pub font: PathBuf,
+
+    ///JSON file of key and button bindings, fifteen/bindings.json in config directory if not set
+    #[arg(long, value_name = "FILE")]
+    pub bindings: Option<PathBuf>,
+
+    ///How far the board is shuffled: easy, medium or hard
+    #[arg(long, default_value_t)]
+    pub difficulty: Difficulty,
+
+    ///Order of solved board: standard, blank-first, snake or spiral
+    #[arg(long, default_value_t)]
+    pub goal: GoalLayout,
+
+    ///Edges of the board: flat, or torus where the zero field goes across them
+    #[arg(long, default_value_t)]
+    pub topology: Topology,
+
+    ///Play board of the day, same for everyone
+    #[arg(long, conflicts_with_all = ["load", "replay", "seed", "size", "difficulty", "goal", "topology"])]
+    pub daily: bool,
+
+    ///UTC date of the daily challenge as YYYY-MM-DD, today if not set
+    #[arg(long, requires = "daily", value_parser = parse_date)]
+    pub date: Option<u64>,
+
+    ///Continue game saved with F5
+    #[arg(long, value_name = "SAVE", conflicts_with_all = ["replay", "seed", "size", "difficulty", "goal", "topology"])]
+    pub load: Option<PathBuf>,
+
+    ///Watch recorded game, with --headless only check it
+    #[arg(long, value_name = "FILE", conflicts_with_all = ["seed", "size", "difficulty", "goal", "topology"])]
+    pub replay: Option<PathBuf>,
+
+    ///Play in terminal without window
+    #[arg(long)]
+    pub headless: bool,
+
+    ///Percent of the cell the sliding tile goes per frame
+    #[arg(long, default_value_t = 10.0, value_parser = parse_positive)]
+    pub animation_speed: f64,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    ///Find optimal solutions of boards, one board per line in flat or FEN format
+    Solve {
+        ///File with boards, stdin if not set
+        input: Option<PathBuf>,
+
+        ///Goal layout of all boards
+        #[arg(long, default_value_t)]
+        goal: GoalLayout,
+
+        ///Topology of all boards: flat or torus
+        #[arg(long, default_value_t)]
+        topology: Topology,
+
+        ///Print every result as JSON line
+        #[arg(long)]
+        json: bool,
+
+        ///Give up a board after this count of expanded nodes
+        #[arg(long)]
+        max_nodes: Option<u64>,
+    },
+    ///Make boards with optimal solution of given length, checked by the solver
+    Generate {
+        ///Count of boards
+        #[arg(long, short = 'n', default_value_t = 1)]
+        count: usize,
+
+        ///Width and height of the boards
+        #[arg(long, default_value_t = DEFAULT_SIZE, value_parser = parse_size)]
+        size: usize,
+
+        ///Optimal solution length: exact `20` or range `18-22`
+        #[arg(long, value_parser = parse_length)]
+        length: RangeInclusive<usize>,
+
+        ///Seed of the first board, random if not set
+        #[arg(long)]
+        seed: Option<u64>,
+
+        ///Goal layout of the boards
+        #[arg(long, default_value_t)]
+        goal: GoalLayout,
+
+        ///Text format of the boards: flat, fen or display
+        #[arg(long, default_value_t = BoardFormat::Flat)]
+        format: BoardFormat,
+
+        ///Skip a board when the solver expands this count of nodes
+        #[arg(long)]
+        max_nodes: Option<u64>,
+    },
+    ///Draw board, its solution or a replay as SVG
+    Svg {
+        ///Board in flat, display or FEN format
+        #[arg(required_unless_present = "replay", conflicts_with = "replay")]
+        board: Option<String>,
+
+        ///Draw optimal solution of the board as frames
+        #[arg(long)]
+        solve: bool,
+
+        ///Draw moves of recorded game
+        #[arg(long, value_name = "FILE")]
+        replay: Option<PathBuf>,
+
+        ///One board with sliding tiles instead of frames
+        #[arg(long)]
+        animate: bool,
+
+        ///Seconds of every move in animation
+        #[arg(long, default_value_t = 0.5, value_parser = parse_positive)]
+        seconds: f64,
+
+        ///Goal layout of the board
+        #[arg(long, default_value_t)]
+        goal: GoalLayout,
+
+        ///Colors: light or dark
+        #[arg(long, default_value_t)]
+        theme: Theme,
+
+        ///File to write, stdout if not set
+        #[arg(long, short = 'o')]
+        output: Option<PathBuf>,
+    },
+    ///Make animated GIF of optimal solution of the board or of a replay
+    Gif {
+        ///Board in flat, display or FEN format
+        #[arg(required_unless_present = "replay", conflicts_with = "replay")]
+        board: Option<String>,
+
+        ///Moves of recorded game instead of the solution
+        #[arg(long, value_name = "FILE")]
+        replay: Option<PathBuf>,
+
+        ///Frames per second
+        #[arg(long, default_value_t = 25, value_parser = clap::value_parser!(u32).range(1..=50))]
+        fps: u32,
+
+        ///Seconds of every move
+        #[arg(long, default_value_t = 0.5, value_parser = parse_positive)]
+        seconds: f64,
+
+        ///Side of the board in pixels
+        #[arg(long, default_value_t = 440, value_parser = clap::value_parser!(u32).range(40..=2000))]
+        size: u32,
+
+        ///Goal layout of the board
+        #[arg(long, default_value_t)]
+        goal: GoalLayout,
+
+        ///Colors: light or dark
+        #[arg(long, default_value_t)]
+        theme: Theme,
+
+        ///File to write
+        #[arg(long, short = 'o')]
+        output: PathBuf,
+    },
+}
+
+fn parse_size(s: &str) -> Result<usize, String> {
+    let size: usize = s.parse().map_err(|e| format!("{}", e))?;
+    if (MIN_SIZE..=MAX_SIZE).contains(&size) {
+        Ok(size)
+    } else {
+        Err(format!("size must be {}..={}", MIN_SIZE, MAX_SIZE))
+    }
+}
+
+fn parse_length(s: &str) -> Result<RangeInclusive<usize>, String> {
+    let (min, max) = match s.find('-') {
+        Some(i) => (&s[..i], &s[i + 1..]),
+        None => (s, s)
+    };
+    let min: usize = min.trim().parse().map_err(|e| format!("{}", e))?;
+    let max: usize = max.trim().parse().map_err(|e| format!("{}", e))?;
+    if min <= max {
+        Ok(min..=max)
+    } else {
+        Err(format!("{} is greater than {}", min, max))
+    }
+}
+
+fn parse_date(s: &str) -> Result<u64, String> {
+    daily::parse_date(s).ok_or_else(|| format!("'{}' is not a date after 1970-01-01 as YYYY-MM-DD", s))
+}
+
+fn parse_positive(s: &str) -> Result<f64, String> {
+    let value: f64 = s.parse().map_err(|e| format!("{}", e))?;
+    if value > 0.0 && value.is_finite() {
+        Ok(value)
+    } else {
+        Err("value must be greater than 0".to_owned())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_test() {
+        let args = Args::try_parse_from(["fifteen", "--size", "3", "--theme", "dark", "--difficulty", "easy"]).unwrap();
+        assert_eq!(args.size, 3);
+        assert_eq!(args.theme, Theme::Dark);
+        assert_eq!(args.difficulty, Difficulty::Easy);
+        assert_eq!(args.animation_speed, 10.0);
+        assert!(Args::try_parse_from(["fifteen", "--size", "1"]).is_err());
+        assert!(Args::try_parse_from(["fifteen", "--animation-speed", "0"]).is_err());
+        assert!(Args::try_parse_from(["fifteen", "--theme", "blue"]).is_err());
+        assert!(Args::try_parse_from(["fifteen", "--load", "a.replay", "--seed", "1"]).is_err());
+        let args = Args::try_parse_from(["fifteen", "--daily", "--date", "2026-10-19"]).unwrap();
+        assert_eq!(args.date, Some(20_745));
+        assert!(Args::try_parse_from(["fifteen", "--date", "2026-10-19"]).is_err());
+        assert!(Args::try_parse_from(["fifteen", "--daily", "--seed", "1"]).is_err());
+        let args = Args::try_parse_from(["fifteen", "--topology", "torus"]).unwrap();
+        assert_eq!(args.topology, Topology::Torus);
+        assert!(Args::try_parse_from(["fifteen", "--daily", "--topology", "torus"]).is_err());
+        let args = Args::try_parse_from(["fifteen", "solve", "boards.txt", "--json"]).unwrap();
+        assert!(matches!(args.command, Some(Command::Solve { json: true, max_nodes: None, .. })));
+        let args = Args::try_parse_from(["fifteen", "generate", "-n", "5", "--length", "18-22"]).unwrap();
+        assert!(matches!(args.command, Some(Command::Generate { count: 5, size: 4, length, .. }) if length == (18..=22)));
+        assert!(Args::try_parse_from(["fifteen", "generate", "--length", "22-18"]).is_err());
+        assert!(Args::try_parse_from(["fifteen", "generate"]).is_err());
+        let args = Args::try_parse_from(["fifteen", "svg", "1 2 3/4 5 6/7 0 8", "--solve", "--theme", "dark"]).unwrap();
+        assert!(matches!(args.command, Some(Command::Svg { solve: true, theme: Theme::Dark, .. })));
+        assert!(Args::try_parse_from(["fifteen", "svg"]).is_err());
+        assert!(Args::try_parse_from(["fifteen", "svg", "1 2 0 3", "--replay", "a.replay"]).is_err());
+        let args = Args::try_parse_from(["fifteen", "gif", "--replay", "a.replay", "--fps", "10", "-o", "a.gif"]).unwrap();
+        assert!(matches!(args.command, Some(Command::Gif { fps: 10, size: 440, .. })));
+        assert!(Args::try_parse_from(["fifteen", "gif", "1 2 0 3", "--fps", "0", "-o", "a.gif"]).is_err());
+        assert!(Args::try_parse_from(["fifteen", "gif", "1 2 0 3"]).is_err());
+    }
+}
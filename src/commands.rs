@@ -0,0 +1,299 @@
+//! Subcommands which work without window.
+
+use std::io::{self, BufRead, Write};
+use std::ops::RangeInclusive;
+use std::time::Instant;
+
+use piston_fifteen::gameboard::{BoardFormat, Gameboard, SeededRng};
+use piston_fifteen::gameboard_view::{GameboardView, GameboardViewSettings, Theme};
+use piston_fifteen::generator;
+use piston_fifteen::goal::GoalLayout;
+use piston_fifteen::notation::{self, Convention};
+use piston_fifteen::recording::{self, GifEncoder};
+use piston_fifteen::replay::{Origin, Replay, ReplayError};
+use piston_fifteen::software::SoftwareGlyphs;
+use piston_fifteen::solver;
+use piston_fifteen::svg;
+use piston_fifteen::topology::Topology;
+use rand::SeedableRng;
+use serde::Serialize;
+
+///Result of one board in `--json` output
+#[derive(Debug, Default, Serialize)]
+struct Solved {
+    ///1-biased line of the input
+    line: usize,
+    board: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    length: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    moves: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    nodes: Option<u64>,
+    time_ms: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+///Solve every board of `input`, one output line for every board.
+///Returns false if some board wasn't solved
+pub fn solve<R: BufRead, W: Write>(input: R, mut output: W, goal: GoalLayout, topology: Topology, json: bool, max_nodes: Option<u64>) -> io::Result<bool> {
+    let mut all_solved = true;
+    for (i, line) in input.lines().enumerate() {
+        let line = line?;
+        let text = line.trim();
+        if text.is_empty() {
+            continue;
+        }
+        let start = Instant::now();
+        let board = text.parse::<Gameboard>();
+        //`#` is an obstacle too, the line is a comment only if it isn't a board
+        if text.starts_with('#') && board.is_err() {
+            continue;
+        }
+        let result = board
+            .map_err(|e| e.to_string())
+            .and_then(|mut board| {
+                board.goal = goal;
+                board.topology = topology;
+                solver::solve_limited(&board, max_nodes).map_err(|e| e.to_string())
+            });
+        let mut solved = Solved {
+            line: i + 1,
+            board: text.to_owned(),
+            time_ms: start.elapsed().as_secs_f64() * 1000.0,
+            ..Solved::default()
+        };
+        match result {
+            Ok(solution) => {
+                solved.length = Some(solution.moves.len());
+                solved.moves = Some(notation::format_letters(&solution.moves, Convention::Blank));
+                solved.nodes = Some(solution.nodes);
+            }
+            Err(e) => {
+                all_solved = false;
+                solved.error = Some(e);
+            }
+        }
+        if json {
+            let line = serde_json::to_string(&solved).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+            writeln!(output, "{}", line)?;
+        } else {
+            match (&solved.error, &solved.moves) {
+                (Some(e), _) => writeln!(output, "line {}: error: {}", solved.line, e)?,
+                //Solved board has no moves to print
+                (None, moves) => writeln!(output, "line {}: {} {} nodes={} time={:.1}ms",
+                                          solved.line, solved.length.unwrap_or(0),
+                                          moves.as_deref().filter(|m| !m.is_empty()).unwrap_or("-"),
+                                          solved.nodes.unwrap_or(0), solved.time_ms)?,
+            }
+        }
+    }
+    Ok(all_solved)
+}
+
+///Options of `generate`
+pub struct GenerateOptions {
+    pub count: usize,
+    pub size: usize,
+    pub lengths: RangeInclusive<usize>,
+    pub seed: u64,
+    pub goal: GoalLayout,
+    pub format: BoardFormat,
+    pub max_nodes: Option<u64>,
+}
+
+///Write boards in `format`, boards in display format are separated with empty line.
+///Returns false if not all boards were made
+pub fn generate<W: Write>(options: &GenerateOptions, mut output: W) -> io::Result<bool> {
+    let mut rng = SeededRng::seed_from_u64(options.seed);
+    for _ in 0..options.count {
+        let generated = match generator::generate(&mut rng, options.size, options.goal, options.lengths.clone(), options.max_nodes) {
+            Some(generated) => generated,
+            None => return Ok(false)
+        };
+        match options.format {
+            BoardFormat::Display => writeln!(output, "{}", generated.board)?,
+            format => writeln!(output, "{}", generated.board.export(format))?,
+        }
+    }
+    Ok(true)
+}
+
+///Options of `svg`
+pub struct SvgOptions {
+    ///Board text, or replay with its initial board and moves
+    pub board: Option<String>,
+    pub replay: Option<Replay>,
+    pub solve: bool,
+    pub animate: bool,
+    pub seconds: f64,
+    pub goal: GoalLayout,
+    pub theme: Theme,
+}
+
+///Replay of `replay` or of `board` with its optimal solution if `solve` is set
+fn replay_of(board: &Option<String>, replay: &Option<Replay>, solve: bool, goal: GoalLayout) -> io::Result<Replay> {
+    let invalid = |message: String| io::Error::new(io::ErrorKind::InvalidInput, message);
+    match (board, replay) {
+        (_, Some(replay)) => Ok(replay.clone()),
+        (Some(text), None) => {
+            let board: Gameboard = text.parse().map_err(|e| invalid(format!("{}", e)))?;
+            let board = Gameboard { goal, ..board };
+            let moves = if solve {
+                solver::solve(&board).map_err(|e| invalid(format!("{}", e)))?.moves
+            } else {
+                vec![]
+            };
+            let mut replay = Replay::new(Origin::Board(board));
+            for direction in moves {
+                replay.record(direction, 0);
+            }
+            Ok(replay)
+        }
+        (None, None) => Err(invalid("no board is given".to_owned()))
+    }
+}
+
+///Write SVG of the board alone, or with moves of its solution or replay
+pub fn svg<W: Write>(options: &SvgOptions, mut output: W) -> io::Result<bool> {
+    let replay = replay_of(&options.board, &options.replay, options.solve, options.goal)?;
+    //Pictures show moves of the first zero field only
+    if let Some(i) = replay.moves.iter().position(|m| m.blank != 0) {
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, format!("move {} of another zero field can't be drawn", i + 1)));
+    }
+    let (board, moves): (_, Vec<_>) = (replay.initial(), replay.moves.iter().map(|m| m.direction).collect());
+    let settings = GameboardViewSettings::with_theme(options.theme);
+    let picture = if options.animate {
+        svg::animated(&board, &moves, &settings, options.seconds)
+    } else if moves.is_empty() {
+        Ok(svg::board(&board, &settings))
+    } else {
+        svg::strip(&board, &moves, &settings)
+    };
+    let picture = picture.map_err(|svg::IllegalMove(i)| io::Error::new(io::ErrorKind::InvalidInput, format!("move {} can't be done", i + 1)))?;
+    output.write_all(picture.as_bytes())?;
+    Ok(true)
+}
+
+///Options of `gif`
+pub struct GifOptions {
+    ///Board text solved by the solver, or replay with its initial board and moves
+    pub board: Option<String>,
+    pub replay: Option<Replay>,
+    pub fps: u32,
+    pub seconds: f64,
+    ///Side of the board in pixels
+    pub size: u32,
+    pub goal: GoalLayout,
+    pub theme: Theme,
+}
+
+///Write animated GIF of the optimal solution of the board or of the replay
+pub fn gif<W: Write>(options: &GifOptions, glyphs: &mut SoftwareGlyphs, output: W) -> io::Result<bool> {
+    let replay = replay_of(&options.board, &options.replay, true, options.goal)?;
+    let settings = GameboardViewSettings { size: options.size as f64, ..GameboardViewSettings::with_theme(options.theme) };
+    //Nothing is written for wrong replay
+    replay.board_at(replay.moves.len()).map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e.to_string()))?;
+    let fps = options.fps as f64;
+    let [width, height] = settings.window_size();
+    let mut encoder = GifEncoder::new(output, width as u32, height as u32, fps)?;
+    recording::frames(&replay, &GameboardView::new(settings), glyphs, fps, options.seconds, |frame| encoder.push(frame))
+        .map_err(|e| match e {
+            ReplayError::Io(e) => e,
+            e => io::Error::new(io::ErrorKind::InvalidInput, e.to_string())
+        })?;
+    encoder.finish()?;
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use piston_fifteen::software;
+
+    #[test]
+    fn solve_test() {
+        let input = "1 2 3/4 5 6/7 0 8\n\n# comment\n1 2 3 4 5 6 7 9 0\n1,2,3/4,5,6/7,8,0\n";
+        let mut output = vec![];
+        assert!(!solve(input.as_bytes(), &mut output, GoalLayout::Standard, Topology::Flat, false, None).unwrap());
+        let output = String::from_utf8(output).unwrap();
+        let lines: Vec<&str> = output.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert!(lines[0].starts_with("line 1: 1 R nodes=1 time="), "{}", lines[0]);
+        assert_eq!(lines[1], "line 4: error: 9 is out of range 0..=8");
+        assert!(lines[2].starts_with("line 5: 0 - nodes=0 time="), "{}", lines[2]);
+
+        //Board beginning with an obstacle isn't a comment
+        let mut output = vec![];
+        assert!(!solve("# comment\n# 1/2 0".as_bytes(), &mut output, GoalLayout::Standard, Topology::Flat, false, None).unwrap());
+        let output = String::from_utf8(output).unwrap();
+        assert_eq!(output.lines().count(), 1);
+        assert!(output.starts_with("line 2: error: boards with obstacles"), "{}", output);
+
+        //Zero field goes across the edge of torus
+        let mut output = vec![];
+        assert!(solve("1 2 3/4 5 6/0 8 7".as_bytes(), &mut output, GoalLayout::Standard, Topology::Torus, false, None).unwrap());
+        assert!(String::from_utf8(output).unwrap().starts_with("line 1: 1 L nodes="));
+
+        let mut output = vec![];
+        assert!(solve("1 2 3/4 5 6/7 0 8".as_bytes(), &mut output, GoalLayout::Standard, Topology::Flat, true, None).unwrap());
+        let value: serde_json::Value = serde_json::from_slice(&output).unwrap();
+        assert_eq!(value["length"], 1);
+        assert_eq!(value["moves"], "R");
+        assert_eq!(value["board"], "1 2 3/4 5 6/7 0 8");
+        assert!(value.get("error").is_none());
+    }
+
+    #[test]
+    fn generate_test() {
+        let mut options = GenerateOptions {
+            count: 3, size: 3, lengths: 8..=12, seed: 4, goal: GoalLayout::Standard, format: BoardFormat::Fen, max_nodes: None,
+        };
+        let mut output = vec![];
+        assert!(generate(&options, &mut output).unwrap());
+        let mut solved = vec![];
+        assert!(solve(&output[..], &mut solved, GoalLayout::Standard, Topology::Flat, true, None).unwrap());
+        let solved: Vec<serde_json::Value> = solved.split(|b| *b == b'\n')
+            .filter(|line| !line.is_empty())
+            .map(|line| serde_json::from_slice(line).unwrap())
+            .collect();
+        assert_eq!(solved.len(), 3);
+        assert!(solved.iter().all(|v| (8..=12).contains(&v["length"].as_u64().unwrap())));
+
+        let mut again = vec![];
+        generate(&options, &mut again).unwrap();
+        assert_eq!(again, output);
+        options.size = 2;
+        options.lengths = 7..=7;
+        assert!(!generate(&options, io::sink()).unwrap());
+    }
+
+    #[test]
+    fn svg_test() {
+        let mut options = SvgOptions {
+            board: Some("1 2 3/4 5 6/0 7 8".to_owned()), replay: None, solve: true, animate: false,
+            seconds: 0.5, goal: GoalLayout::Standard, theme: Theme::Light,
+        };
+        let mut output = vec![];
+        assert!(svg(&options, &mut output).unwrap());
+        let picture = String::from_utf8(output).unwrap();
+        assert_eq!(picture.matches("marker-end").count(), 2);
+        options.board = Some("1 2 3/4 5 6/8 7 0".to_owned());
+        assert!(svg(&options, io::sink()).is_err());
+    }
+
+    #[test]
+    fn gif_test() {
+        let mut glyphs = software::glyphs("assets/amazone.ttf").unwrap();
+        let mut options = GifOptions {
+            board: Some("1 2/0 3".to_owned()), replay: None, fps: 10, seconds: 0.2,
+            size: 100, goal: GoalLayout::Standard, theme: Theme::Dark,
+        };
+        let mut output = vec![];
+        assert!(gif(&options, &mut glyphs, &mut output).unwrap());
+        assert!(output.starts_with(b"GIF89a"));
+        options.board = Some("2 1/0 3".to_owned());
+        assert!(gif(&options, &mut glyphs, io::sink()).is_err());
+    }
+}
@@ -0,0 +1,87 @@
+//! Daily challenge: the board is made from the UTC date, so it is the same for everyone.
+//! Days are counted from unix epoch, as `stats::today()` does.
+
+use crate::gameboard::Gameboard;
+use crate::replay::{Origin, Replay};
+
+///Seed of the board of `day`, days next to each other give unrelated seeds
+pub fn seed(day: u64) -> u64 {
+    //splitmix64
+    let mut z = day.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+///Replay to start the challenge of `day` from
+pub fn replay(day: u64) -> Replay {
+    Replay::new(Origin::Seed(seed(day)))
+}
+
+///Board of the challenge of `day`
+pub fn board(day: u64) -> Gameboard {
+    replay(day).initial()
+}
+
+///`YYYY-MM-DD` of `day`
+pub fn date(day: u64) -> String {
+    //Civil from days, March based years make leap day the last one
+    let z = day as i64 + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z.rem_euclid(146_097);
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let d = day_of_year - (153 * mp + 2) / 5 + 1;
+    let m = if mp < 10 { mp + 3 } else { mp - 9 };
+    let y = year_of_era + era * 400 + if m <= 2 { 1 } else { 0 };
+    format!("{:04}-{:02}-{:02}", y, m, d)
+}
+
+///Day of `YYYY-MM-DD` date, None for dates before 1970 or not valid
+pub fn parse_date(text: &str) -> Option<u64> {
+    let parts: Vec<i64> = text.trim().split('-').map(|p| p.parse().ok()).collect::<Option<_>>()?;
+    let (year, m, d) = match parts[..] {
+        [year, m, d] if (1..=12).contains(&m) && d >= 1 => (year, m, d),
+        _ => return None
+    };
+    let y = if m <= 2 { year - 1 } else { year };
+    let era = y.div_euclid(400);
+    let year_of_era = y.rem_euclid(400);
+    let mp = if m > 2 { m - 3 } else { m + 9 };
+    let day_of_year = (153 * mp + 2) / 5 + d - 1;
+    let day = era * 146_097 + year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year - 719_468;
+    //Day 31 of 30-day month would be the next month
+    if day < 0 || date(day as u64) != format!("{:04}-{:02}-{:02}", year, m, d) {
+        return None;
+    }
+    Some(day as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn date_test() {
+        assert_eq!(date(0), "1970-01-01");
+        assert_eq!(date(11_016), "2000-02-29");
+        assert_eq!(date(20_745), "2026-10-19");
+        for day in (0..30_000).step_by(7) {
+            assert_eq!(parse_date(&date(day)), Some(day));
+        }
+        assert_eq!(parse_date("2026-02-29"), None);
+        assert_eq!(parse_date("1969-12-31"), None);
+        assert_eq!(parse_date("2026-13-01"), None);
+        assert_eq!(parse_date("yesterday"), None);
+    }
+
+    #[test]
+    fn seed_test() {
+        assert_eq!(replay(20_745).initial(), board(20_745));
+        //Everyone gets this board, in every build
+        let expected: Gameboard = "7 4 12 3/11 8 5 9/15 1 6 0/2 10 13 14".parse().unwrap();
+        assert_eq!(board(20_745).cells, expected.cells);
+        assert_ne!(seed(20_745), seed(20_746));
+    }
+}
@@ -0,0 +1,44 @@
+//! How far from the goal a new board is made.
+
+#[derive(Debug, Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Hash, Default)]
+pub enum Difficulty {
+    ///Few random moves from the goal
+    Easy,
+    ///More random moves from the goal
+    Medium,
+    ///Fully shuffled board
+    #[default]
+    Hard,
+}
+
+impl Difficulty {
+    pub const ALL: [Difficulty; 3] = [Difficulty::Easy, Difficulty::Medium, Difficulty::Hard];
+
+    ///Count of random moves from the goal for board `size`, None for full shuffle
+    pub fn scramble_moves(self, size: usize) -> Option<usize> {
+        match self {
+            Difficulty::Easy => Some(size * size),
+            Difficulty::Medium => Some(3 * size * size),
+            Difficulty::Hard => None,
+        }
+    }
+}
+
+names!(Difficulty, "difficulty", {
+    Easy => "easy",
+    Medium => "medium",
+    Hard => "hard",
+});
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn name_test() {
+        for difficulty in &Difficulty::ALL {
+            assert_eq!(difficulty.to_string().parse::<Difficulty>(), Ok(*difficulty));
+        }
+        assert!("insane".parse::<Difficulty>().is_err());
+    }
+}
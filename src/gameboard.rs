@@ -1,35 +1,95 @@
 extern crate rand;
 
-use rand::thread_rng;
+use rand::{thread_rng, Rng, SeedableRng};
 use rand::seq::SliceRandom;
+use rand_pcg::Pcg32;
+use std::error::Error;
 use std::fmt;
-
-pub const SIZE: usize = 4;
-pub const FSIZE: f64 = 4.0;
+use std::ops::{Index, IndexMut};
+use std::str::FromStr;
+use crate::animator::Direction;
+use crate::difficulty::Difficulty;
+use crate::goal::GoalLayout;
+use crate::topology::Topology;
+
+///Classic board width
+pub const DEFAULT_SIZE: usize = 4;
+pub const MIN_SIZE: usize = 2;
+///Biggest board, 16x16 is still drawn smoothly and solved by the human method
+pub const MAX_SIZE: usize = 16;
+///Value of immovable obstacle cell, `#` in text formats
+pub const OBSTACLE: u16 = 0;
+///Generator of seeded boards, same seed gives same board in every build
+pub type SeededRng = Pcg32;
+///Obstacle in parsed values, zero field is `0` there
+const WALL: u32 = u32::MAX;
 
 ///
-/// Current cells with [[u8; SIZE]; SIZE]
-/// may be inappropriate, and [u8;SIZE*SIZE]
-/// with easy linear arithmetic (web-assembly plain style) looks good.
+/// Cells are `size * size` values in row-major order
+/// with easy linear arithmetic (web-assembly plain style).
+/// Use `Position` instead of counting index by hand.
+/// Classic board has one zero field, boards of puzzle designers may have several of them and obstacles.
 ///
 #[derive(Debug, Clone, Eq, PartialEq, Hash, Default)]
 pub struct Gameboard {
-    pub cells: [[u8; SIZE]; SIZE],
+    ///Width and height of the board
+    pub size: usize,
+    pub cells: Vec<u16>,
     pub moves: usize,
+    ///Order of tiles which wins this game
+    pub goal: GoalLayout,
+    ///Which cells are next to each other
+    pub topology: Topology,
+}
+
+///0-biased place of the cell on the board
+#[derive(Debug, Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Hash, Default)]
+pub struct Position {
+    pub row: usize,
+    pub col: usize,
+}
+
+impl Position {
+    pub fn new(row: usize, col: usize) -> Self {
+        Position { row, col }
+    }
+
+    ///Position of `index` in cells of board with width `size`
+    pub fn from_index(index: usize, size: usize) -> Self {
+        Position { row: index / size, col: index % size }
+    }
+
+    ///Index in cells of board with width `size`
+    pub fn index(self, size: usize) -> usize {
+        self.row * size + self.col
+    }
+}
+
+impl Index<Position> for Gameboard {
+    type Output = u16;
+
+    fn index(&self, position: Position) -> &u16 {
+        &self.cells[position.index(self.size)]
+    }
+}
+
+impl IndexMut<Position> for Gameboard {
+    fn index_mut(&mut self, position: Position) -> &mut u16 {
+        let size = self.size;
+        &mut self.cells[position.index(size)]
+    }
 }
 
 impl fmt::Display for Gameboard {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let transposed = self.transpose_flatten();
-
-        let res: Vec<String> = transposed.iter()
-            .map(|x| Gameboard::normalize(*x))
+        let res: Vec<String> = self.cells.iter()
+            .map(|x| self.normalize(*x))
             .collect();
 
         let mut result = "".to_owned();
         for (i, _) in res.iter().enumerate() {
             result += &res[i];
-            if (i + 1) % 4 == 0 {
+            if (i + 1) % self.size == 0 {
                 result += "\n"
             } else {
                 result += " ";
@@ -41,93 +101,396 @@ impl fmt::Display for Gameboard {
 }
 
 
+///Text formats of the board. Zero field is `0` in all of them, except `Display`, obstacle is `#`
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum BoardFormat {
+    ///Row-major in one line: `1 2 3 ... 15 0`
+    Flat,
+    ///Same as `Display`: padded rows, zero field is empty
+    Display,
+    ///Rows separated with `/`: `1,2,3,4/5,6,7,8/9,10,11,12/13,14,15,0`
+    Fen,
+}
+
+impl BoardFormat {
+    pub const ALL: [BoardFormat; 3] = [BoardFormat::Flat, BoardFormat::Display, BoardFormat::Fen];
+}
+
+impl fmt::Display for BoardFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            BoardFormat::Flat => "flat",
+            BoardFormat::Display => "display",
+            BoardFormat::Fen => "fen",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl FromStr for BoardFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        BoardFormat::ALL.iter()
+            .copied()
+            .find(|b| b.to_string() == s.trim())
+            .ok_or_else(|| format!("unknown board format '{}'", s))
+    }
+}
+
+///Size of the board is taken from count of rows or values
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum ParseBoardError {
+    Empty,
+    ///Count of values in flat format isn't a square
+    NotSquare(usize),
+    ///Board width isn't in MIN_SIZE..=MAX_SIZE
+    WrongSize(usize),
+    ///Row with this 0-biased index doesn't have `expected` values
+    RowLength { row: usize, found: usize, expected: usize },
+    NotANumber(String),
+    OutOfRange { value: u32, max: usize },
+    ///Value found twice, so `missing` values are not on the board
+    Duplicate { value: u16, missing: Vec<u16> },
+    NoZero,
+    ///Tiles are numbered from 1 without gaps, these numbers are skipped
+    Missing(Vec<u16>),
+}
+
+impl fmt::Display for ParseBoardError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseBoardError::Empty => write!(f, "board is empty"),
+            ParseBoardError::NotSquare(n) => write!(f, "{} values can't make a square board", n),
+            ParseBoardError::WrongSize(n) =>
+                write!(f, "board {0}x{0} is not supported, size must be {1}..={2}", n, MIN_SIZE, MAX_SIZE),
+            ParseBoardError::RowLength { row, found, expected } =>
+                write!(f, "row {} has {} values, expected {}", row + 1, found, expected),
+            ParseBoardError::NotANumber(v) => write!(f, "'{}' is not a number", v),
+            ParseBoardError::OutOfRange { value, max } => write!(f, "{} is out of range 0..={}", value, max),
+            ParseBoardError::Duplicate { value, missing } => {
+                let missing: Vec<String> = missing.iter().map(|v| v.to_string()).collect();
+                write!(f, "{} is found more than once, missing: {}", value, missing.join(", "))
+            }
+            ParseBoardError::NoZero => write!(f, "board has no zero field"),
+            ParseBoardError::Missing(missing) => {
+                let missing: Vec<String> = missing.iter().map(|v| v.to_string()).collect();
+                write!(f, "tiles are missing: {}", missing.join(", "))
+            }
+        }
+    }
+}
+
+impl Error for ParseBoardError {}
+
+///
+/// Parse any of `BoardFormat`. Format is detected by `/` or by count of lines.
+///
+impl FromStr for Gameboard {
+    type Err = ParseBoardError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let lines: Vec<&str> = s.lines().filter(|l| !l.trim().is_empty()).collect();
+        let (size, values) = if s.contains('/') {
+            let rows: Vec<&str> = s.trim().split('/').collect();
+            (rows.len(), Gameboard::parse_rows(&rows, false)?)
+        } else if lines.len() > 1 {
+            (lines.len(), Gameboard::parse_rows(&lines, true)?)
+        } else if lines.is_empty() {
+            return Err(ParseBoardError::Empty);
+        } else {
+            let values = Gameboard::parse_values(lines[0])?;
+            let size = (values.len() as f64).sqrt().round() as usize;
+            if size * size != values.len() {
+                return Err(ParseBoardError::NotSquare(values.len()));
+            }
+            (size, values)
+        };
+        if !(MIN_SIZE..=MAX_SIZE).contains(&size) {
+            return Err(ParseBoardError::WrongSize(size));
+        }
+        let cells = Gameboard::check_values(size, &values)?;
+        Ok(Gameboard::from_rows(size, &cells))
+    }
+}
+
+
 ///
 /// May be some functions should contains in controller?!
 ///
 impl Gameboard {
     ///
-    /// Generate new gameboard with shuffled numbers
+    /// Generate new classic gameboard with shuffled numbers
     ///
     pub fn new() -> Self {
-        let vec = Gameboard::shuffle_vec();
-        let mut cells = [[0; SIZE]; SIZE];
-        for i in 0..SIZE {
-            for j in 0..SIZE {
-                cells[i][j] = vec[i * SIZE + j];
+        let mut board = Gameboard::from_columns(DEFAULT_SIZE, &Gameboard::shuffle_vec());
+        board.make_solvable();
+        board
+    }
+
+    ///
+    /// Generate classic gameboard shuffled with seeded generator. Same seed gives same board.
+    ///
+    pub fn from_seed(seed: u64) -> Self {
+        Gameboard::generate(seed, DEFAULT_SIZE, GoalLayout::Standard, Difficulty::Hard)
+    }
+
+    ///Seeded gameboard which can be solved into `goal`, scrambled as `difficulty` says
+    pub fn generate(seed: u64, size: usize, goal: GoalLayout, difficulty: Difficulty) -> Self {
+        let mut rng = SeededRng::seed_from_u64(seed);
+        match difficulty.scramble_moves(size) {
+            None => {
+                let mut board = Gameboard::from_columns(size, &Gameboard::shuffle_vec_with(size, &mut rng));
+                board.goal = goal;
+                board.make_solvable();
+                board
+            }
+            Some(moves) => {
+                let mut board = Gameboard::solved(size, goal);
+                let mut previous: Option<Direction> = None;
+                while board.moves < moves {
+                    let direction = Direction::ALL[rng.gen_range(0, 4)];
+                    //Step back makes walk shorter
+                    if previous != Some(direction.opposite()) && board.move_zero(direction) {
+                        previous = Some(direction);
+                    }
+                }
+                board.moves = 0;
+                board
             }
         }
-        Gameboard { cells, moves: 0 }
     }
 
-    /// return 0-biased zero field (x,y). Zero has value of 16
-    pub fn zero(&self) -> [usize; 2] {
-        for x in 0..4 {
-            for y in 0..4 {
-                if self.cells[x][y] == 16 {
-                    return [x, y];
+    ///Board in order of `goal`
+    pub fn solved(size: usize, goal: GoalLayout) -> Self {
+        Gameboard { size, cells: goal.cells(size), moves: 0, goal, topology: Topology::Flat }
+    }
+
+    /// Build gameboard with width `size` from row-major values. Zero has value of `size * size`
+    pub fn from_rows(size: usize, rows: &[u16]) -> Self {
+        Gameboard { size, cells: rows.to_vec(), moves: 0, goal: GoalLayout::Standard, topology: Topology::Flat }
+    }
+
+    ///Value of zero field
+    pub fn blank(&self) -> u16 {
+        (self.size * self.size) as u16
+    }
+
+    ///Board in text `format`
+    pub fn export(&self, format: BoardFormat) -> String {
+        let blank = self.blank();
+        let text: Vec<String> = self.cells.iter()
+            .map(|v| match *v {
+                OBSTACLE => "#".to_owned(),
+                v if v == blank => "0".to_owned(),
+                v => v.to_string(),
+            })
+            .collect();
+        match format {
+            BoardFormat::Flat => text.join(" "),
+            BoardFormat::Display => self.to_string(),
+            BoardFormat::Fen => text.chunks(self.size)
+                .map(|row| row.join(","))
+                .collect::<Vec<String>>()
+                .join("/"),
+        }
+    }
+
+    ///Row-major values of rows, in `Display` rows zero fields may be left empty
+    fn parse_rows(rows: &[&str], padded: bool) -> Result<Vec<u32>, ParseBoardError> {
+        let size = rows.len();
+        let width = Gameboard::cell_width(size);
+        let mut values = vec![];
+        for (i, row) in rows.iter().enumerate() {
+            let mut row_values = Gameboard::parse_values(row)?;
+            let empty = size.saturating_sub(row_values.len());
+            if padded && empty > 0 && empty < size {
+                //Empty cells: every cell takes width and a space, find the columns without value
+                let row = row.trim_end();
+                let mut columns: Vec<usize> = (0..size)
+                    .filter(|c| row.get(c * (width + 1)..c * (width + 1) + width).is_none_or(|cell| cell.trim().is_empty()))
+                    .collect();
+                if columns.len() != empty {
+                    columns = (size - empty..size).collect();
                 }
+                for column in columns {
+                    row_values.insert(column, 0);
+                }
+            }
+            if row_values.len() != size {
+                return Err(ParseBoardError::RowLength { row: i, found: row_values.len(), expected: size });
             }
+            values.extend(row_values);
         }
-        panic!("No zero value found")
+        Ok(values)
+    }
+
+    ///Values separated with whitespace or commas. `0`, `_` and `.` are zero field, `#` is obstacle
+    fn parse_values(text: &str) -> Result<Vec<u32>, ParseBoardError> {
+        text.split(|c: char| c.is_whitespace() || c == ',')
+            .filter(|v| !v.is_empty())
+            .map(|v| match v {
+                "_" | "." => Ok(0),
+                "#" => Ok(WALL),
+                v => v.parse::<u32>().map_err(|_| ParseBoardError::NotANumber(v.to_owned()))
+            })
+            .collect()
+    }
+
+    ///Tiles 1..=count of them must be found once, zero fields and obstacles may be many.
+    ///Zero becomes `size * size`
+    fn check_values(size: usize, values: &[u32]) -> Result<Vec<u16>, ParseBoardError> {
+        let count = size * size;
+        if let Some(value) = values.iter().find(|v| **v != WALL && **v as usize >= count) {
+            return Err(ParseBoardError::OutOfRange { value: *value, max: count - 1 });
+        }
+        let tiles = values.iter().filter(|v| **v != 0 && **v != WALL).count();
+        let mut seen = vec![0; count];
+        for v in values.iter().filter(|v| **v != WALL) {
+            seen[*v as usize] += 1;
+        }
+        let missing: Vec<u16> = (1..=tiles).filter(|v| seen[*v] == 0).map(|v| v as u16).collect();
+        if let Some(value) = (1..count).find(|v| seen[*v] > 1) {
+            return Err(ParseBoardError::Duplicate { value: value as u16, missing });
+        }
+        if seen[0] == 0 {
+            return Err(ParseBoardError::NoZero);
+        }
+        if !missing.is_empty() {
+            return Err(ParseBoardError::Missing(missing));
+        }
+        Ok(values.iter()
+            .map(|v| match *v {
+                0 => count as u16,
+                WALL => OBSTACLE,
+                v => v as u16,
+            })
+            .collect())
+    }
+
+    ///Column-major values, so seeds give the same boards as before row-major cells
+    fn from_columns(size: usize, columns: &[u16]) -> Self {
+        let mut cells = vec![0; size * size];
+        for (i, value) in columns.iter().enumerate().take(size * size) {
+            cells[Position::new(i % size, i / size).index(size)] = *value;
+        }
+        Gameboard { size, cells, moves: 0, goal: GoalLayout::Standard, topology: Topology::Flat }
+    }
+
+    /// return 0-biased zero field, the first one in row-major order if there are several.
+    /// Zero has value of `size * size`
+    pub fn zero(&self) -> Position {
+        let blank = self.blank();
+        match self.cells.iter().position(|v| *v == blank) {
+            Some(index) => Position::from_index(index, self.size),
+            None => panic!("No zero value found")
+        }
+    }
+
+    ///All zero fields in row-major order, their numbers in replays are taken from it
+    pub fn zeros(&self) -> Vec<Position> {
+        let blank = self.blank();
+        self.cells.iter().enumerate()
+            .filter(|(_, v)| **v == blank)
+            .map(|(i, _)| Position::from_index(i, self.size))
+            .collect()
+    }
+
+    pub fn is_zero(&self, cell: Position) -> bool {
+        self[cell] == self.blank()
+    }
+
+    pub fn is_obstacle(&self, cell: Position) -> bool {
+        self[cell] == OBSTACLE
+    }
+
+    ///Cell has a tile which can be moved, not zero field or obstacle
+    pub fn is_tile(&self, cell: Position) -> bool {
+        !self.is_zero(cell) && !self.is_obstacle(cell)
+    }
+
+    ///Count of tiles
+    pub fn tiles(&self) -> usize {
+        self.cells.iter().filter(|v| **v != OBSTACLE && **v != self.blank()).count()
+    }
+
+    ///One zero field and no obstacles, as solvers and generated boards have
+    pub fn is_classic(&self) -> bool {
+        self.tiles() + 1 == self.cells.len()
+    }
+
+    pub fn get(&self, position: Position) -> u16 {
+        self[position]
+    }
+
+    pub fn set(&mut self, position: Position, value: u16) {
+        self[position] = value;
     }
 
     /// Shuffle values while init new board
-    fn shuffle_vec() -> Vec<u8> {
-        let mut rng = thread_rng();
-        let mut vec: Vec<u8> = (1..=16).collect();
-        vec.shuffle(&mut rng);
+    fn shuffle_vec() -> Vec<u16> {
+        Gameboard::shuffle_vec_with(DEFAULT_SIZE, &mut thread_rng())
+    }
+
+    fn shuffle_vec_with<R: Rng>(size: usize, rng: &mut R) -> Vec<u16> {
+        let mut vec: Vec<u16> = (1..=(size * size) as u16).collect();
+        vec.shuffle(rng);
         vec
     }
 
+    ///Count of digits in the biggest tile
+    fn cell_width(size: usize) -> usize {
+        (size * size - 1).to_string().len()
+    }
+
     /// Fill empty symbol
-    fn normalize(x: u8) -> String {
-        match x {
-            16 => "  ".to_owned(),
-            v if v < 10 => format!("0{}", v),
-            v => format!("{}", v)
+    fn normalize(&self, x: u16) -> String {
+        let width = Gameboard::cell_width(self.size);
+        if x == self.blank() {
+            " ".repeat(width)
+        } else if x == OBSTACLE {
+            format!("{:>width$}", "#", width = width)
+        } else {
+            format!("{:0width$}", x, width = width)
         }
     }
 
-    pub fn cell_as_string(&self, idx: [usize; 2]) -> String {
-        Self::normalize(self.cells[idx[0]][idx[1]])
+    pub fn cell_as_string(&self, position: Position) -> String {
+        self.normalize(self[position])
     }
 
 
-    ///Checks cells for neighbouring
-    pub fn is_neighbours(first: [usize; 2], second: [usize; 2]) -> bool {
-        if first == second {
-            return false;
-        }
-        //Соседи всегда на одной линии
-        if first[0] != second[0] && first[1] != second[1] {
-            return false;
-        }
-
-        let (i_first, i_second) =
-            ((first[0] as isize, first[1] as isize), (second[0] as isize, second[1] as isize));
+    ///Checks cells for neighbouring, on torus also across the edges
+    pub fn is_neighbours(&self, first: Position, second: Position) -> bool {
+        self.topology.direction(first, second, self.size).is_some()
+    }
 
-        if i_first.0 == i_second.0 && (i_first.1 - i_second.1).abs() == 1 {
-            return true;
+    ///Main action. Swap tile with zero field next to it, the first one in row-major order if there are several
+    pub fn swap_with_zero(&mut self, cell: Position) -> bool {
+        match self.zero_next_to(cell) {
+            Some(zero) => self.slide(cell, zero),
+            None => false
         }
-        if i_first.1 == i_second.1 && (i_first.0 - i_second.0).abs() == 1 {
-            return true;
+    }
+
+    ///Zero field next to tile `cell`, the first one in row-major order
+    pub fn zero_next_to(&self, cell: Position) -> Option<Position> {
+        if !self.is_tile(cell) {
+            return None;
         }
+        self.zeros().into_iter().find(|zero| self.is_neighbours(cell, *zero))
+    }
 
-        false
+    ///Tile `cell` can slide into zero field `zero`
+    pub fn can_slide(&self, cell: Position, zero: Position) -> bool {
+        self.is_tile(cell) && self.is_zero(zero) && self.is_neighbours(cell, zero)
     }
 
-    ///Main action. Swap cells with empty cells
-    pub fn swap_with_zero(&mut self, cell: [usize; 2]) -> bool {
-        let zero = self.zero();
-        // dbg!(cell);
-        //  dbg!(zero);
-        let isn = Gameboard::is_neighbours(cell, zero);
-        //  dbg!("Is_neighbours:{}",isn);
-        if isn {
+    ///Slide tile `cell` into zero field `zero` next to it
+    pub fn slide(&mut self, cell: Position, zero: Position) -> bool {
+        if self.can_slide(cell, zero) {
             self.moves += 1;
-            let temporary = self.cells[zero[0]][zero[1]];
-            self.cells[zero[0]][zero[1]]  = std::mem::replace(& mut self.cells[cell[0]][cell[1]],temporary);
-
+            self.cells.swap(zero.index(self.size), cell.index(self.size));
             true
         } else {
             false
@@ -135,26 +498,116 @@ impl Gameboard {
     }
 
 
-    fn transpose_flatten(&self) -> Vec<u8> {
-        let mut transposed = self.cells;
-        for i in 0..transposed.len() {
-            for j in 0..transposed.len() {
-                transposed[i][j] = self.cells[j][i];
+    /// Field next to `cell` in `direction`, None if it is outside of the board
+    pub fn neighbour(&self, cell: Position, direction: Direction) -> Option<Position> {
+        self.topology.step(cell, direction, self.size)
+    }
+
+    ///Move zero field to `direction`. Returns false if zero is already at the edge
+    pub fn move_zero(&mut self, direction: Direction) -> bool {
+        self.move_blank(self.zero(), direction)
+    }
+
+    ///Move zero field at `zero` to `direction`, there must be a tile
+    pub fn move_blank(&mut self, zero: Position, direction: Direction) -> bool {
+        match self.neighbour(zero, direction) {
+            Some(cell) => self.slide(cell, zero),
+            None => false
+        }
+    }
+
+    ///Cells of the won game. Tiles go in order of the goal layout around obstacles,
+    ///zero fields take the last places of it
+    pub fn goal_cells(&self) -> Vec<u16> {
+        let goal = self.goal.cells(self.size);
+        let mut order: Vec<usize> = (0..goal.len()).collect();
+        order.sort_by_key(|i| goal[*i]);
+        let mut cells = vec![self.blank(); goal.len()];
+        let mut tiles = (1..=self.tiles() as u16).peekable();
+        for i in order {
+            if self.cells[i] == OBSTACLE {
+                cells[i] = OBSTACLE;
+            } else if let Some(tile) = tiles.next() {
+                cells[i] = tile;
             }
         }
-        transposed.iter().flatten().copied().collect::<Vec<u8>>()
+        cells
     }
 
-    //Need to implemnt
+    ///Tiles are in order of the goal, obstacles stay where they are
     pub fn is_over(&self) -> bool {
-        fn check_order(vec: &[u8], value: u8) -> bool {
-            match vec.first() {
-                None => true,
-                Some(current) => value < *current && check_order(&vec[1..], *current)
+        self.cells == self.goal_cells()
+    }
+
+    ///Tiles standing on their goal places, zero fields and obstacles are never placed
+    pub fn placed(&self) -> Vec<bool> {
+        let blank = self.blank();
+        self.cells.iter().zip(self.goal_cells())
+            .map(|(cell, goal)| *cell != blank && *cell != OBSTACLE && *cell == goal)
+            .collect()
+    }
+
+    ///Sum of Manhattan distances of tiles from their goal places, on torus the shorter way around
+    pub fn distance(&self) -> usize {
+        let goal = self.goal_cells();
+        let mut place = vec![0; goal.len() + 1];
+        for (i, v) in goal.iter().enumerate() {
+            place[*v as usize] = i;
+        }
+        let blank = self.blank();
+        self.cells.iter().enumerate()
+            .filter(|(_, v)| **v != blank && **v != OBSTACLE)
+            .map(|(i, v)| {
+                let (from, to) = (Position::from_index(i, self.size), Position::from_index(place[*v as usize], self.size));
+                self.topology.distance(from.row, to.row, self.size) + self.topology.distance(from.col, to.col, self.size)
+            })
+            .sum()
+    }
+
+    ///
+    /// Board can be solved when parity of permutation from the goal (zero included)
+    /// is the same as parity of distance between zero field and its goal place.
+    /// On odd torus a move across the edge changes the permutation but not the distance parity,
+    /// so every board is solvable. Boards with obstacles or several zero fields aren't checked,
+    /// their designer makes them solvable.
+    ///
+    pub fn is_solvable(&self) -> bool {
+        if !self.is_classic() || (self.topology == Topology::Torus && self.size % 2 == 1) {
+            return true;
+        }
+        let count = self.size * self.size;
+        let goal = self.goal.cells(self.size);
+        let mut target = vec![0; count + 1];
+        for (i, v) in goal.iter().enumerate() {
+            target[*v as usize] = i;
+        }
+        //Parity by cycles of permutation: cycle of length n gives n-1 transpositions
+        let mut visited = vec![false; count];
+        let mut transpositions = 0;
+        for start in 0..count {
+            let mut i = start;
+            let mut length = 0;
+            while !visited[i] {
+                visited[i] = true;
+                i = target[self.cells[i] as usize];
+                length += 1;
             }
+            transpositions += length.max(1) - 1;
+        }
+        let zero = self.zero();
+        let goal_zero = self.goal.position_of(self.blank(), self.size);
+        let distance = (zero.row as isize - goal_zero.row as isize).abs()
+            + (zero.col as isize - goal_zero.col as isize).abs();
+        transpositions % 2 == distance as usize % 2
+    }
+
+    ///Swap two first tiles if the board can't be solved
+    pub fn make_solvable(&mut self) {
+        if !self.is_solvable() {
+            let blank = self.blank();
+            let tiles: Vec<usize> = (0..self.cells.len()).filter(|i| self.cells[*i] != blank).take(2).collect();
+            self.cells.swap(tiles[0], tiles[1]);
         }
-        let v = self.transpose_flatten();
-        check_order(&v, 0)
     }
 }
 
@@ -170,8 +623,7 @@ mod tests {
     #[test]
     fn new_test() {
         let gameboard = Gameboard::new();
-        let gameboard_flatten = gameboard.cells.iter()
-            .flat_map(|x| vec![x[0], x[1], x[2], x[3]]).collect::<Vec<u8>>();
+        let gameboard_flatten = gameboard.cells.to_vec();
         //  dbg!(&gameboard);
         //Каждое число встречается не более одного раза
         for x in &gameboard_flatten {
@@ -183,23 +635,28 @@ mod tests {
     fn zero_test_smoke() {
         for _ in 0..100 {
             let gameboard = Gameboard::new();
-            assert!(gameboard.zero()[0] <= 3);
-            assert!(gameboard.zero()[1] <= 3);
+            assert!(gameboard.zero().row <= 3);
+            assert!(gameboard.zero().col <= 3);
         }
     }
 
     #[test]
     fn is_neighbours_test() {
-        let c1 = [2_usize, 3_usize];
-        let c2 = [1_usize, 0_usize];
-        let c3 = [2_usize, 2_usize];
-        let c4 = [1_usize, 3_usize];
-        assert_eq!(Gameboard::is_neighbours(c1, c2), false);
-        assert_eq!(Gameboard::is_neighbours(c1, c3), true);
-        assert_eq!(Gameboard::is_neighbours(c1, c4), true);
-        assert_eq!(Gameboard::is_neighbours(c2, c3), false);
-        assert_eq!(Gameboard::is_neighbours(c2, c4), false);
-        assert_eq!(Gameboard::is_neighbours(c2, c3), false);
+        let c1 = Position::new(3, 2);
+        let c2 = Position::new(0, 1);
+        let c3 = Position::new(2, 2);
+        let c4 = Position::new(3, 1);
+        let mut board = Gameboard::solved(4, GoalLayout::Standard);
+        assert!(!board.is_neighbours(c1, c2));
+        assert!(board.is_neighbours(c1, c3));
+        assert!(board.is_neighbours(c1, c4));
+        assert!(!board.is_neighbours(c2, c3));
+        assert!(!board.is_neighbours(c2, c4));
+        assert!(!board.is_neighbours(c2, c3));
+        //Top and bottom rows are joined on torus
+        board.topology = Topology::Torus;
+        assert!(board.is_neighbours(c2, c4));
+        assert!(!board.is_neighbours(c2, c3));
     }
 
     #[test]
@@ -208,22 +665,121 @@ mod tests {
             let mut g = Gameboard::new();
 
             let zero = g.zero();
-            if zero == [2, 2] {
-                let before = g.cells[2][3];
+            if zero == Position::new(2, 2) {
+                let before = g[Position::new(3, 2)];
                 println!("before");
                 println!("{}", g);
-                assert_eq!(g.swap_with_zero([2, 3]), true);
+                assert!(g.swap_with_zero(Position::new(3, 2)));
                 println!("after");
                 println!("{}", g);
-                assert_eq!(g.cells[2][2], before);
-                assert_eq!(g.cells[2][3], 16);
+                assert_eq!(g[Position::new(2, 2)], before);
+                assert_eq!(g[Position::new(3, 2)], 16);
+            }
+        }
+    }
+
+    #[test]
+    fn from_seed_test() {
+        assert_eq!(Gameboard::from_seed(42), Gameboard::from_seed(42));
+        assert_ne!(Gameboard::from_seed(42).cells, Gameboard::from_seed(43).cells);
+    }
+
+    #[test]
+    fn from_rows_test() {
+        let g = Gameboard::new();
+        assert_eq!(Gameboard::from_rows(4, &g.cells), g);
+    }
+
+    #[test]
+    fn move_zero_test() {
+        let mut g = Gameboard::from_rows(4, &(1..=16).collect::<Vec<u16>>());
+        assert!(!g.move_zero(Direction::Right));
+        assert!(!g.move_zero(Direction::Bottom));
+        assert!(g.move_zero(Direction::Top));
+        assert_eq!(g.zero(), Position::new(2, 3));
+        assert_eq!(g.get(Position::new(3, 3)), 12);
+        assert!(g.move_zero(Direction::Left));
+        assert_eq!(g.zero(), Position::new(2, 2));
+        assert_eq!(g.moves, 2);
+    }
+
+    #[test]
+    fn parse_formats_test() {
+        for seed in 0..50 {
+            let g = Gameboard::from_seed(seed);
+            for format in &[BoardFormat::Flat, BoardFormat::Display, BoardFormat::Fen] {
+                let text = g.export(*format);
+                assert_eq!(text.parse::<Gameboard>().unwrap(), g, "{}", text);
+            }
+        }
+        let solved = Gameboard::from_rows(4, &(1..=16).collect::<Vec<u16>>());
+        assert_eq!(solved.export(BoardFormat::Flat), "1 2 3 4 5 6 7 8 9 10 11 12 13 14 15 0");
+        assert_eq!(solved.export(BoardFormat::Fen), "1,2,3,4/5,6,7,8/9,10,11,12/13,14,15,0");
+        assert_eq!("1 2 3 4/5 6 7 8/9 10 11 12/13 14 15 _".parse::<Gameboard>().unwrap(), solved);
+        assert_eq!("01 02 03 04\n05 06 07 08\n09 10 11 12\n13 14 15".parse::<Gameboard>().unwrap(), solved);
+    }
+
+    #[test]
+    fn parse_errors_test() {
+        assert_eq!("".parse::<Gameboard>(), Err(ParseBoardError::Empty));
+        assert_eq!("1 2 3".parse::<Gameboard>(), Err(ParseBoardError::NotSquare(3)));
+        assert_eq!("1 2 3 4/5 6 7 8/9 10 11 12".parse::<Gameboard>(),
+                   Err(ParseBoardError::RowLength { row: 0, found: 4, expected: 3 }));
+        assert_eq!("1 2 3 4/5 6 7/8 9 10 11 12/13 14 15 0".parse::<Gameboard>(),
+                   Err(ParseBoardError::RowLength { row: 1, found: 3, expected: 4 }));
+        assert_eq!("0".parse::<Gameboard>(), Err(ParseBoardError::WrongSize(1)));
+        assert_eq!("1 2 3 4 5 6 7 8 9 10 11 12 13 14 15 x".parse::<Gameboard>(),
+                   Err(ParseBoardError::NotANumber("x".to_owned())));
+        assert_eq!("1 2 3 4 5 6 7 8 9 10 11 12 13 14 15 16".parse::<Gameboard>(), Err(ParseBoardError::OutOfRange { value: 16, max: 15 }));
+        let duplicate = "1 2 3 4 5 6 7 8 9 10 11 12 13 14 14 0".parse::<Gameboard>().unwrap_err();
+        assert_eq!(duplicate, ParseBoardError::Duplicate { value: 14, missing: vec![15] });
+        assert_eq!(duplicate.to_string(), "14 is found more than once, missing: 15");
+    }
+
+    #[test]
+    fn goal_test() {
+        for goal in &GoalLayout::ALL {
+            let mut g = Gameboard::from_rows(4, &goal.cells(4));
+            g.goal = *goal;
+            assert!(g.is_over());
+            assert!(g.is_solvable());
+            g.cells.swap(1, 2);
+            assert!(!g.is_over());
+            assert!(!g.is_solvable());
+            for seed in 0..20 {
+                let g = Gameboard::generate(seed, 4, *goal, Difficulty::Hard);
+                assert!(g.is_solvable());
+            }
+        }
+        let mut g = Gameboard::from_rows(4, &GoalLayout::Standard.cells(4));
+        g.goal = GoalLayout::Snake;
+        assert!(!g.is_over());
+        assert!(!g.is_solvable());
+        g.goal = GoalLayout::Standard;
+        assert!(g.move_zero(Direction::Top));
+        assert!(g.is_solvable());
+    }
+
+    #[test]
+    fn generate_test() {
+        for size in MIN_SIZE..=MAX_SIZE {
+            for difficulty in &Difficulty::ALL {
+                let g = Gameboard::generate(size as u64, size, GoalLayout::Spiral, *difficulty);
+                assert_eq!(g, Gameboard::generate(size as u64, size, GoalLayout::Spiral, *difficulty));
+                assert_eq!(g.cells.len(), size * size);
+                assert_eq!(g.moves, 0);
+                assert!(g.is_solvable());
+                assert_eq!(g.export(BoardFormat::Display).parse::<Gameboard>().unwrap().cells, g.cells);
             }
         }
+        let g = Gameboard::generate(1, 3, GoalLayout::Standard, Difficulty::Easy);
+        assert_eq!(g.export(BoardFormat::Fen).split('/').count(), 3);
+        assert_eq!(Gameboard::solved(3, GoalLayout::Standard).export(BoardFormat::Flat), "1 2 3 4 5 6 7 8 0");
     }
 
     #[test]
     fn display_show() {
-        let mut g = Gameboard::new();
+        let g = Gameboard::new();
         println!("{}", g);
     }
 
@@ -231,8 +787,8 @@ mod tests {
     fn sell_as_string_test() {
         for _ in 0..100 {
             let g = Gameboard::new();
-            let cs = g.cell_as_string([2, 2]);
-            let cu = g.cells[2][2];
+            let cs = g.cell_as_string(Position::new(2, 2));
+            let cu = g.get(Position::new(2, 2));
             match cu {
                 16 => assert_eq!(&cs[..], "  "),
                 1..=9 => assert_eq!(&cs, &format!("0{}", cu)),
@@ -242,34 +798,83 @@ mod tests {
     }
 
     #[test]
-    fn transpose_test() {
-        let g = Gameboard::new();
-        let tr = g.transpose_flatten();
-        let c = g.cells;
-        let manual_tr = vec![
-            c[0][0], c[1][0], c[2][0], c[3][0],
-            c[0][1], c[1][1], c[2][1], c[3][1],
-            c[0][2], c[1][2], c[2][2], c[3][2],
-            c[0][3], c[1][3], c[2][3], c[3][3]];
-        assert_eq!(tr, manual_tr);
+    fn position_test() {
+        let mut g = Gameboard::new();
+        for index in 0..16 {
+            let position = Position::from_index(index, 4);
+            assert_eq!(position.index(4), index);
+            assert_eq!(g.get(position), g.cells[position.row * 4 + position.col]);
+        }
+        g.set(Position::new(1, 3), 42);
+        assert_eq!(g.cells[7], 42);
+        g[Position::new(3, 1)] = 24;
+        assert_eq!(g.cells[13], 24);
     }
 
     #[test]
     fn is_over_test() {
         for _ in 0..100 {
             let g = Gameboard::new();
-            assert_eq!(g.is_over(), false);
+            assert!(!g.is_over());
         }
         let mut g = Gameboard::new();
-        assert_eq!(g.is_over(), false);
-        let mut cells = [[0; SIZE]; SIZE];
-        for i in 0..SIZE {
-            for j in 0..SIZE {
-                cells[j][i] = i as u8 * 4 + j as u8 + 1;
-            }
-        }
-        g.cells = cells;
+        assert!(!g.is_over());
+        g.cells = (1..=16).collect();
         println!("{}", g);
-        assert_eq!(g.is_over(), true);
+        assert!(g.is_over());
+    }
+
+    #[test]
+    fn placed_test() {
+        let g: Gameboard = "1 2 3/4 5 6/7 0 8".parse().unwrap();
+        assert_eq!(g.placed().iter().filter(|p| **p).count(), 7);
+        assert_eq!(g.distance(), 1);
+        let g: Gameboard = "0 2 1/4 5 6/7 8 3".parse().unwrap();
+        assert_eq!(g.placed(), vec![false, true, false, true, true, true, true, true, false]);
+        assert_eq!(g.distance(), 2 + 2);
+        assert_eq!(Gameboard::solved(4, GoalLayout::Spiral).distance(), 0);
+    }
+
+    #[test]
+    fn torus_test() {
+        let mut g: Gameboard = "1 2 3/4 5 6/7 8 0".parse().unwrap();
+        g.topology = Topology::Torus;
+        assert!(g.move_zero(Direction::Right));
+        assert_eq!(g.zero(), Position::new(2, 0));
+        assert_eq!(g.get(Position::new(2, 2)), 7);
+        //7 is one step away across the edge
+        assert_eq!(g.distance(), 1);
+        assert!(g.move_zero(Direction::Bottom));
+        assert_eq!(g.zero(), Position::new(0, 0));
+        //Odd torus can swap two tiles, even one can't
+        let g: Gameboard = "2 1 3/4 5 6/7 8 0".parse().unwrap();
+        assert!(!g.is_solvable());
+        assert!(Gameboard { topology: Topology::Torus, ..g }.is_solvable());
+        let g: Gameboard = "2 1 3 4/5 6 7 8/9 10 11 12/13 14 15 0".parse().unwrap();
+        assert!(!Gameboard { topology: Topology::Torus, ..g }.is_solvable());
+    }
+
+    #[test]
+    fn designer_test() {
+        let mut g: Gameboard = "1 2 3/4 # 0/6 5 0".parse().unwrap();
+        assert_eq!(g.cells, vec![1, 2, 3, 4, OBSTACLE, 9, 6, 5, 9]);
+        assert_eq!(g.zeros(), vec![Position::new(1, 2), Position::new(2, 2)]);
+        assert_eq!((g.tiles(), g.is_classic()), (6, false));
+        //Tiles go around the obstacle, zero fields are the last ones
+        assert_eq!(g.goal_cells(), vec![1, 2, 3, 4, OBSTACLE, 5, 6, 9, 9]);
+        assert!(!g.move_blank(Position::new(1, 2), Direction::Left));
+        assert!(!g.move_blank(Position::new(1, 2), Direction::Bottom));
+        assert!(g.move_blank(Position::new(2, 2), Direction::Left));
+        assert_eq!(g.export(BoardFormat::Fen), "1,2,3/4,#,0/6,0,5");
+        assert_eq!(g.zero_next_to(Position::new(2, 2)), Some(Position::new(1, 2)));
+        assert!(g.swap_with_zero(Position::new(2, 2)));
+        assert!(g.is_over());
+        assert_eq!(g.placed().iter().filter(|p| **p).count(), 6);
+        for format in &BoardFormat::ALL {
+            assert_eq!(g.export(*format).parse::<Gameboard>().unwrap().cells, g.cells, "{}", g.export(*format));
+        }
+        assert_eq!("1 2 0 0".parse::<Gameboard>().unwrap().zeros().len(), 2);
+        assert_eq!("1 2 # #".parse::<Gameboard>(), Err(ParseBoardError::NoZero));
+        assert_eq!("1 3 # 0".parse::<Gameboard>(), Err(ParseBoardError::Missing(vec![2])));
     }
 }
@@ -1,18 +1,141 @@
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::sync::Arc;
+use std::thread;
 use super::Gameboard;
-use piston::input::{GenericEvent, Button, MouseButton, Key};
+use crate::difficulty::Difficulty;
+use crate::gameboard::Position;
+use crate::goal::GoalLayout;
+use piston::input::{GenericEvent, Button, Key};
 use crate::gameboard_controller::GameState::{GameOver, InProcess};
 use crate::Animator;
 use crate::animator::Direction;
 use crate::animator::Direction::*;
+use crate::replay::{Origin, Replay, ReplayError, ReplayPlayer, REPLAY_DIR, SAVE_DIR};
+use crate::stats::{self, GameRecord, StatsStore};
+use crate::leaderboard::{Leaderboard, Score};
+use crate::pack::{Pack, Progress};
+use crate::solver::{self, Solution, SolveError};
+use crate::strategy::{self, SubGoal};
+use crate::gameboard_view::Theme;
+use crate::bindings::{Action, Bindings};
+use crate::tutorial::{Advance, Tutorial};
+
+///Cursor moves less than this count of pixels in a click
+const CLICK_DISTANCE: f64 = 3.0;
+///Cells per second of dropped tile going back
+const SNAP_BACK_SPEED: f64 = 8.0;
+
+///Tile dragged by mouse to zero field
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Drag {
+    pub cell: Position,
+    ///Zero field the tile goes to
+    pub zero: Position,
+    ///Cursor where the tile was pressed
+    start: [f64; 2],
+    ///Part of the way to zero field, from 0 to 1
+    pub offset: f64,
+    ///Tile was dropped before the half way and goes back
+    pub released: bool,
+}
+
+///Solver gives up after this count of nodes, big boards may have no rating
+const SOLVER_NODES: u64 = 20_000_000;
+///Hint search gives up after this count of nodes and takes the move of the human method
+const HINT_NODES: u64 = 200_000;
+
+///Search in background thread, it is canceled when the job is dropped
+struct Job<T> {
+    receiver: Receiver<T>,
+    cancel: Arc<AtomicBool>,
+}
+
+impl<T: Send + 'static> Job<T> {
+    ///Run `search` with the cancel flag, None result isn't sent
+    fn spawn<F: FnOnce(&AtomicBool) -> Option<T> + Send + 'static>(search: F) -> Self {
+        let (sender, receiver) = mpsc::channel();
+        let cancel = Arc::new(AtomicBool::new(false));
+        let flag = Arc::clone(&cancel);
+        thread::spawn(move || {
+            if let Some(result) = search(&flag) {
+                let _ = sender.send(result);
+            }
+        });
+        Job { receiver, cancel }
+    }
+}
+
+impl<T> Drop for Job<T> {
+    fn drop(&mut self) {
+        self.cancel.store(true, Ordering::Relaxed);
+    }
+}
 
 pub struct GameboardController {
     pub gameboard: Gameboard,
-    pub selected: Option<[usize; 2]>,
+    pub selected: Option<Position>,
     pub game_state: GameState,
     cursor_pos: [f64; 2],
     pub animator: Box<dyn Animator>,
     pub animate_direction: Direction,
-    pub animate_cell:[usize; 2]
+    pub animate_cell: Position,
+    ///Recording of current game
+    pub replay: Replay,
+    ///Where the finished game was saved
+    pub replay_path: Option<PathBuf>,
+    pub player: Option<ReplayPlayer>,
+    ///Seconds in game
+    elapsed: f64,
+    ///Finished games are added here, if it is opened
+    pub stats: Option<StatsStore>,
+    pub difficulty: Difficulty,
+    pub hints: usize,
+    pub leaderboard: Option<Leaderboard>,
+    ///Name typed on game over screen, None when it isn't asked
+    pub name_input: Option<String>,
+    ///State to return from statistics and leaderboard screens
+    previous_state: GameState,
+    ///Day of the daily challenge, if this game is one
+    pub daily: Option<u64>,
+    pub packs: Vec<Pack>,
+    ///Solved levels, if it is opened
+    pub progress: Option<Progress>,
+    ///Pack and level chosen on level select screen
+    pub level_cursor: (usize, usize),
+    ///Pack and level of current game
+    pub level: Option<(usize, usize)>,
+    ///Optimal solution length of the initial board, when solver has found it
+    pub optimal: Option<usize>,
+    ///Solver works in background thread from the game start
+    solver: Option<Job<usize>>,
+    ///Record of finished game waiting for the solver
+    unsaved: Option<GameRecord>,
+    ///Search of the hint for the board it was asked on
+    hint_search: Option<Job<(Gameboard, Option<Solution>)>>,
+    pub drag: Option<Drag>,
+    pub bindings: Bindings,
+    ///Direction of the stick push, next move by stick waits for its release
+    stick: Option<Direction>,
+    ///Clock is stopped and the board is hidden
+    pub paused: bool,
+    ///Moves of zero fields taken back with their numbers, the last one is redone first
+    undone: Vec<(usize, Direction)>,
+    ///Theme of the view, switched by action
+    pub theme: Theme,
+    ///Window should be closed
+    pub quit: bool,
+    ///Picture of the solved board is asked to be saved
+    pub screenshot: bool,
+    ///Overlay of placed tiles is switched from the one of the theme
+    pub overlay: bool,
+    ///Lesson and step, while the tutorial is played
+    pub tutorial: Option<Tutorial>,
+    ///Game to go back to after the tutorial
+    before_tutorial: Option<Replay>,
+    ///Why the hinted tile goes next, when the hint is taken from the human method
+    pub explanation: Option<SubGoal>,
 }
 
 #[derive(Debug, Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Hash)]
@@ -20,28 +143,59 @@ pub enum GameState {
     Prepare,
     InProcess,
     GameOver,
+    Replay,
+    Stats,
+    Leaderboard,
+    LevelSelect,
 }
 
 ///Maybe some functions should be remove from model to controller
 impl GameboardController {
     pub fn new(gameboard: Gameboard, animator: Box<dyn Animator>) -> Self {
+        let replay = Replay::new(Origin::Board(gameboard.clone()));
         GameboardController { gameboard, selected: None, game_state: GameState::Prepare,
-            cursor_pos: [0_f64; 2], animator, animate_direction: Direction::Top, animate_cell: [0,0] }
+            cursor_pos: [0_f64; 2], animator, animate_direction: Direction::Top, animate_cell: Position::default(),
+            replay, replay_path: None, player: None, elapsed: 0.0,
+            stats: None, difficulty: Difficulty::default(), hints: 0,
+            leaderboard: None, name_input: None, previous_state: GameState::Prepare, daily: None,
+            packs: vec![], progress: None, level_cursor: (0, 0), level: None,
+            optimal: None, solver: None, unsaved: None, hint_search: None, drag: None,
+            bindings: Bindings::default(), stick: None, paused: false,
+            undone: vec![], theme: Theme::default(), quit: false, screenshot: false, overlay: false,
+            tutorial: None, before_tutorial: None, explanation: None }
+    }
+
+    ///New game on board generated from `seed`, replay keeps only seed
+    pub fn from_seed(seed: u64, animator: Box<dyn Animator>) -> Self {
+        let mut controller = GameboardController::new(Gameboard::from_seed(seed), animator);
+        controller.replay = Replay::new(Origin::Seed(seed));
+        controller
+    }
+
+    ///Continue game saved as replay: all its moves are done, time goes on from the last one
+    pub fn from_replay(replay: Replay, animator: Box<dyn Animator>) -> Result<Self, ReplayError> {
+        let gameboard = replay.board_at(replay.moves.len())?;
+        let mut controller = GameboardController::new(gameboard, animator);
+        controller.elapsed = replay.moves.last().map_or(0.0, |m| m.at as f64 / 1000.0);
+        controller.difficulty = replay.difficulty;
+        controller.replay = replay;
+        Ok(controller)
+    }
+
+    ///Save unfinished game to continue it later
+    pub fn save(&self) {
+        match self.replay.save_in(SAVE_DIR) {
+            Ok(path) => println!("game saved to {}", path.display()),
+            Err(e) => println!("game wasn't saved: {}", e)
+        }
     }
 
     ///Main function. Swap two neighbour cells, if one is zero cell.
-    fn swap_rectangle_or_cancel(&mut self, cell: [usize; 2], prev_cell: [usize; 2]) {
+    fn swap_rectangle_or_cancel(&mut self, cell: Position, prev_cell: Position) {
         //Если предыдушая анимация завершилась
         if self.animator.is_over() {
-            if self.gameboard.zero() == cell {
-                self.animate_cell=cell;
-                self.fetch_direction(cell, prev_cell);
-                if self.gameboard.swap_with_zero(prev_cell){
-                    self.animator.start();
-                }
-                if self.gameboard.is_over() {
-                    self.game_state = GameOver;
-                }
+            if self.animate_swap(prev_cell, cell) {
+                self.moved(cell, self.animate_direction.opposite());
             }
             println!("moves: {}", self.gameboard.moves);
             println!("{}", self.gameboard);
@@ -49,15 +203,453 @@ impl GameboardController {
         }
     }
 
-    fn fetch_direction(&mut self, cell: [usize; 2], prev_cell: [usize; 2]){
-        if cell[0] > prev_cell[0] {
-            self.animate_direction = Right;
-        } else if cell[0] < prev_cell[0] {
-            self.animate_direction = Left;
-        } else if cell[1] > prev_cell[1] {
-            self.animate_direction = Bottom;
+    ///Record done move of zero field from `zero` and finish the game if it is solved
+    fn moved(&mut self, zero: Position, direction: Direction) {
+        let at = (self.elapsed * 1000.0) as u64;
+        //Number of the zero field before the move: zero fields before its old place, except itself at the new one
+        let moved_to = self.gameboard.neighbour(zero, direction);
+        let blank = self.gameboard.zeros().into_iter().filter(|z| Some(*z) != moved_to && *z < zero).count();
+        self.replay.record_blank(blank, direction, at);
+        //Move done again keeps the rest of undone moves
+        if self.undone.last() == Some(&(blank, direction)) {
+            self.undone.pop();
+        } else {
+            self.undone.clear();
+        }
+        self.explanation = None;
+        if let Some(tutorial) = self.tutorial.as_mut() {
+            match tutorial.update(&self.gameboard) {
+                Advance::Lesson => self.play_lesson(),
+                Advance::Finished => self.end_tutorial(),
+                Advance::Stay | Advance::Step => {}
+            }
+        } else if self.gameboard.is_over() {
+            self.finish();
+        }
+    }
+
+    ///Tutorial lets tile of `cell` be moved, else it tells why not
+    fn is_allowed(&mut self, cell: Position) -> bool {
+        let tile = self.gameboard.get(cell);
+        self.tutorial.as_mut().is_none_or(|tutorial| tutorial.allows(tile))
+    }
+
+    ///Slide tile `cell` into zero field `zero` and start animation of it
+    fn animate_swap(&mut self, cell: Position, zero: Position) -> bool {
+        if self.gameboard.can_slide(cell, zero) && self.is_allowed(cell) && self.gameboard.slide(cell, zero) {
+            self.animate_cell = zero;
+            self.fetch_direction(zero, cell);
+            self.animator.start();
+            true
+        } else {
+            false
+        }
+    }
+
+    ///Find optimal solution of the initial board without blocking the game
+    fn start_solver(&mut self) {
+        self.stop_solver();
+        let board = self.replay.initial();
+        self.optimal = None;
+        self.solver = Some(Job::spawn(move |cancel| {
+            solver::solve_cancelable(&board, Some(SOLVER_NODES), cancel).ok().map(|solution| solution.moves.len())
+        }));
+    }
+
+    ///Take optimal length if the solver has finished, the record of finished game is saved with it
+    fn poll_solver(&mut self) {
+        let result = match self.solver.as_ref().map(|job| job.receiver.try_recv()) {
+            Some(Err(TryRecvError::Empty)) | None => return,
+            Some(result) => result,
+        };
+        self.optimal = result.ok();
+        self.solver = None;
+        self.save_record();
+    }
+
+    ///Cancel the solver of the previous game, its record is saved without optimal length
+    fn stop_solver(&mut self) {
+        self.solver = None;
+        self.save_record();
+    }
+
+    ///Solver is still looking for optimal length of the game
+    pub fn is_computing(&self) -> bool {
+        self.solver.is_some()
+    }
+
+    ///Add record of finished game to stats
+    fn save_record(&mut self) {
+        if let Some(mut record) = self.unsaved.take() {
+            record.optimal = self.optimal;
+            record.stars = self.reference().map(|reference| stats::stars(record.moves, reference));
+            if let Some(stats) = self.stats.as_mut() {
+                if let Err(e) = stats.add(record) {
+                    println!("stats weren't saved: {}", e);
+                }
+            }
+        }
+    }
+
+    ///Par of the pack level, or optimal length for other games
+    pub fn reference(&self) -> Option<usize> {
+        let par = self.level.and_then(|(pack, level)| self.packs[pack].levels[level].par);
+        par.or(self.optimal)
+    }
+
+    ///Stars for current moves, None if there is nothing to compare with
+    pub fn stars(&self) -> Option<u8> {
+        self.reference().map(|reference| stats::stars(self.gameboard.moves, reference))
+    }
+
+    fn finish(&mut self) {
+        self.game_state = GameOver;
+        match self.replay.save_in(REPLAY_DIR) {
+            Ok(path) => {
+                println!("replay saved to {}", path.display());
+                self.replay_path = Some(path);
+            }
+            Err(e) => println!("replay wasn't saved: {}", e)
+        }
+        //Record waits for optimal length, if the solver hasn't found it yet
+        self.unsaved = Some(self.record());
+        if self.solver.is_none() {
+            self.save_record();
+        }
+        if let (Some((pack, level)), Some(progress)) = (self.level, self.progress.as_mut()) {
+            if let Err(e) = progress.complete(&self.packs[pack].id, level) {
+                println!("progress wasn't saved: {}", e);
+            }
+        }
+        if let Some(leaderboard) = &self.leaderboard {
+            self.name_input = Some(leaderboard.last_name().unwrap_or("").to_owned());
+        }
+    }
+
+    ///Add score with typed name to the leaderboard
+    fn submit_name(&mut self) {
+        let name = match self.name_input.take() {
+            Some(name) if !name.trim().is_empty() => name.trim().to_owned(),
+            Some(_) => "Player".to_owned(),
+            None => return
+        };
+        let score = Score {
+            name,
+            size: self.gameboard.size,
+            moves: self.gameboard.moves,
+            time: self.elapsed,
+            finished_at: stats::now(),
+        };
+        if let Some(leaderboard) = self.leaderboard.as_mut() {
+            if let Err(e) = leaderboard.add(score) {
+                println!("score wasn't saved: {}", e);
+            }
+        }
+    }
+
+    ///Stats record of current game
+    pub fn record(&self) -> GameRecord {
+        let seed = match self.replay.origin {
+            Origin::Seed(seed) => Some(seed),
+            Origin::Board(_) => None,
+        };
+        GameRecord {
+            size: self.gameboard.size,
+            seed,
+            difficulty: self.difficulty.to_string(),
+            moves: self.gameboard.moves,
+            time: self.elapsed,
+            hints: self.hints,
+            optimal: self.optimal,
+            finished_at: stats::now(),
+            daily: self.daily,
+            stars: self.stars(),
+        }
+    }
+
+    ///Seconds from the start of the game
+    pub fn elapsed(&self) -> f64 {
+        self.elapsed
+    }
+
+    ///Open statistics or leaderboard `screen` or return from it
+    pub fn toggle_screen(&mut self, screen: GameState) {
+        if self.game_state == screen {
+            self.game_state = self.previous_state;
         } else {
-            self.animate_direction = Top;
+            if self.game_state != GameState::Stats && self.game_state != GameState::Leaderboard {
+                self.previous_state = self.game_state;
+            }
+            self.game_state = screen;
+        }
+    }
+
+    ///Start playback of `replay` from initial board
+    pub fn play(&mut self, replay: Replay) {
+        self.gameboard = replay.initial();
+        self.player = Some(ReplayPlayer::new(replay));
+        self.selected = None;
+        self.game_state = GameState::Replay;
+    }
+
+    ///Level under cursor can be played
+    pub fn is_unlocked(&self, pack: usize, level: usize) -> bool {
+        match &self.progress {
+            Some(progress) => progress.is_unlocked(&self.packs[pack].id, level),
+            None => true
+        }
+    }
+
+    pub fn is_completed(&self, pack: usize, level: usize) -> bool {
+        self.progress.as_ref().is_some_and(|p| p.is_completed(&self.packs[pack].id, level))
+    }
+
+    ///Start new game on level of pack
+    pub fn play_level(&mut self, pack: usize, level: usize) -> Result<(), String> {
+        self.start_board(self.packs[pack].levels[level].board()?);
+        self.level = Some((pack, level));
+        if self.packs[pack].levels[level].par.is_none() {
+            self.start_solver();
+        }
+        Ok(())
+    }
+
+    ///New game on `gameboard` which isn't daily or a level
+    fn start_board(&mut self, gameboard: Gameboard) {
+        self.stop_solver();
+        self.replay = Replay::new(Origin::Board(gameboard.clone()));
+        self.gameboard = gameboard;
+        self.level = None;
+        self.daily = None;
+        self.paused = false;
+        self.undone.clear();
+        self.selected = None;
+        self.explanation = None;
+        self.drag = None;
+        self.elapsed = 0.0;
+        self.hints = 0;
+        self.replay_path = None;
+        self.name_input = None;
+        self.animator.stop();
+        self.optimal = None;
+        self.hint_search = None;
+        self.game_state = InProcess;
+    }
+
+    ///Begin lessons of the human method, the game before them comes back after the last one
+    pub fn start_tutorial(&mut self) {
+        self.before_tutorial = Some(self.replay.clone());
+        self.tutorial = Some(Tutorial::new());
+        self.play_lesson();
+    }
+
+    ///Start scripted board of current lesson
+    fn play_lesson(&mut self) {
+        if let Some(board) = self.tutorial.as_ref().map(Tutorial::board) {
+            self.start_board(board);
+        }
+    }
+
+    ///Leave the tutorial for the game before it
+    fn end_tutorial(&mut self) {
+        self.tutorial = None;
+        if let Some(replay) = self.before_tutorial.take() {
+            self.gameboard = replay.board_at(replay.moves.len()).unwrap_or_else(|_| replay.initial());
+            self.replay = replay;
+        }
+        self.animator.stop();
+        self.game_state = GameState::Prepare;
+    }
+
+    ///Do bound action in game, only pause works while game is paused
+    pub fn act(&mut self, action: Action) {
+        match action {
+            Action::Pause => self.paused = !self.paused,
+            _ if self.paused => {}
+            Action::Restart => self.restart(),
+            Action::Overlay => self.overlay = !self.overlay,
+            Action::Hint => self.hint(),
+            Action::Deselect => self.selected = None,
+            //Start is on prepare screen, select needs cursor, theme and quit work everywhere
+            Action::Start | Action::Select | Action::Theme | Action::Quit => {}
+            //Tile goes on moving or is dragged by mouse
+            _ if !self.animator.is_over() || self.drag.is_some() => {}
+            Action::Move(direction) => self.slide_zero(0, direction.opposite()),
+            Action::Undo => self.undo(),
+            Action::Redo => {
+                if let Some((blank, direction)) = self.undone.last().copied() {
+                    self.slide_zero(blank, direction);
+                }
+            }
+        }
+    }
+
+    ///Move zero field number `blank` with animation of the tile, keys move the first one
+    fn slide_zero(&mut self, blank: usize, direction: Direction) {
+        let zero = match self.gameboard.zeros().get(blank) {
+            Some(zero) => *zero,
+            None => return
+        };
+        let tile = self.gameboard.neighbour(zero, direction);
+        if tile.is_some_and(|cell| self.animate_swap(cell, zero)) {
+            self.selected = None;
+            self.moved(zero, direction);
+        }
+    }
+
+    ///Take back the last move, it is removed from the replay too
+    fn undo(&mut self) {
+        let last = match self.replay.moves.last() {
+            Some(last) => *last,
+            None => return
+        };
+        //Tile goes back to the place the zero field had before the move
+        let before = self.replay.board_at(self.replay.moves.len() - 1).ok();
+        let tile = match before.and_then(|board| board.zeros().get(last.blank).copied()) {
+            Some(tile) => tile,
+            None => return
+        };
+        let zero = self.gameboard.neighbour(tile, last.direction);
+        if zero.is_some_and(|zero| self.animate_swap(tile, zero)) {
+            self.replay.moves.pop();
+            self.undone.push((last.blank, last.direction));
+            self.gameboard.moves = self.replay.moves.len();
+            self.selected = None;
+        }
+    }
+
+    ///Look for the tile to move next in background, it is selected when the search ends
+    fn hint(&mut self) {
+        //Tutorial shows the human method, boards with obstacles have no optimal solution
+        if self.tutorial.is_some() || !self.gameboard.is_classic() {
+            self.show_hint(None);
+            return;
+        }
+        if self.hint_search.is_some() {
+            return;
+        }
+        let board = self.gameboard.clone();
+        self.hint_search = Some(Job::spawn(move |cancel| {
+            match solver::solve_cancelable(&board, Some(HINT_NODES), cancel) {
+                Err(SolveError::Canceled) => None,
+                solution => Some((board, solution.ok()))
+            }
+        }));
+    }
+
+    ///Show the hint of finished search, if the board is still the one it was asked on
+    fn poll_hint(&mut self) {
+        let result = match self.hint_search.as_ref().map(|job| job.receiver.try_recv()) {
+            Some(Err(TryRecvError::Empty)) | None => return,
+            Some(result) => result,
+        };
+        self.hint_search = None;
+        if let Ok((board, solution)) = result {
+            if board.cells == self.gameboard.cells {
+                self.show_hint(solution);
+            }
+        }
+    }
+
+    ///Select the first tile of optimal solution. Tutorial and big boards get the move of the human method with its sub-goal,
+    ///the best estimate is taken if the goal layout has no human method or the board has obstacles
+    fn show_hint(&mut self, optimal: Option<Solution>) {
+        let board = &self.gameboard;
+        self.explanation = None;
+        let zero = board.zero();
+        let estimate = |b: &Gameboard| if b.is_classic() { solver::heuristic(b) } else { b.distance() };
+        let tile = match optimal {
+            Some(solution) => solution.moves.first().and_then(|d| board.neighbour(zero, *d)),
+            None => match strategy::solve(board) {
+                Ok(moves) => {
+                    self.explanation = moves.first().map(|m| m.goal);
+                    moves.first().and_then(|m| board.neighbour(zero, m.direction))
+                }
+                Err(_) => board.zeros().into_iter()
+                    .flat_map(|zero| Direction::ALL.iter().map(move |d| (zero, *d)))
+                    .filter_map(|(zero, d)| {
+                        let mut next = board.clone();
+                        if next.move_blank(zero, d) { Some((estimate(&next), next.neighbour(zero, d))) } else { None }
+                    })
+                    .min()
+                    .and_then(|(_, tile)| tile)
+            }
+        };
+        if let Some(tile) = tile {
+            self.selected = Some(tile);
+            self.hints += 1;
+        }
+    }
+
+    ///Start the board again, moves and time are counted from zero
+    fn restart(&mut self) {
+        self.gameboard = self.replay.initial();
+        self.replay.moves.clear();
+        self.undone.clear();
+        self.elapsed = 0.0;
+        self.hints = 0;
+        self.selected = None;
+        self.explanation = None;
+        self.drag = None;
+        self.animator.stop();
+        //Lesson begins from its first step
+        if let Some(tutorial) = self.tutorial.as_mut() {
+            tutorial.step = 0;
+            tutorial.feedback = None;
+        }
+    }
+
+    ///Zero field where the sliding tile comes from, it is the new place of the moved zero field
+    pub fn moving_zero(&self) -> Position {
+        self.gameboard.neighbour(self.animate_cell, self.animate_direction.opposite())
+            .filter(|cell| self.gameboard.is_zero(*cell))
+            .unwrap_or_else(|| self.gameboard.zero())
+    }
+
+    fn fetch_direction(&mut self, cell: Position, prev_cell: Position){
+        self.animate_direction = direction(&self.gameboard, prev_cell, cell);
+    }
+
+    ///Move dragged tile after cursor, only along the way to zero field
+    fn drag_to(&mut self, cell_size: f64) {
+        let cursor = self.cursor_pos;
+        if let Some(drag) = self.drag.as_mut().filter(|d| !d.released) {
+            let (dx, dy) = (drag.zero.col as f64 - drag.cell.col as f64, drag.zero.row as f64 - drag.cell.row as f64);
+            let way = (cursor[0] - drag.start[0]) * dx + (cursor[1] - drag.start[1]) * dy;
+            drag.offset = (way / cell_size).clamp(0.0, 1.0);
+        }
+    }
+
+    ///Mouse is released: tile past the half way goes to zero field, else it goes back
+    fn drop_tile(&mut self) {
+        let drag = match self.drag {
+            Some(drag) if !drag.released => drag,
+            _ => return
+        };
+        let zero = drag.zero;
+        let moved = (self.cursor_pos[0] - drag.start[0]).hypot(self.cursor_pos[1] - drag.start[1]);
+        if drag.offset > 0.5 {
+            self.drag = None;
+            if self.gameboard.slide(drag.cell, zero) {
+                self.animator.stop();
+                self.moved(zero, direction(&self.gameboard, zero, drag.cell));
+            }
+        } else if moved < CLICK_DISTANCE {
+            //Click selects the tile, as before drag
+            self.drag = None;
+            self.selected = Some(drag.cell);
+        } else {
+            self.drag = Some(Drag { released: true, ..drag });
+        }
+    }
+
+    ///Dropped tile goes back by `dt` seconds
+    fn snap_back(&mut self, dt: f64) {
+        if let Some(drag) = self.drag.as_mut().filter(|d| d.released) {
+            drag.offset -= dt * SNAP_BACK_SPEED;
+            if drag.offset <= 0.0 {
+                self.drag = None;
+            }
         }
     }
 
@@ -66,74 +658,604 @@ impl GameboardController {
             //dbg!(pos);
             self.cursor_pos = pos;
         }
+        if let Some(args) = event.update_args() {
+            if self.game_state == InProcess && !self.paused {
+                self.elapsed += args.dt;
+                self.poll_hint();
+            }
+            self.poll_solver();
+        }
+
+        //Typed name has no actions
+        let mut action = match self.name_input {
+            Some(_) => None,
+            None => self.bindings.action(event, self.game_state)
+        };
+        if let (Some(args), InProcess) = (event.controller_axis_args(), self.game_state) {
+            action = action.or(self.bindings.stick_action(args, &mut self.stick));
+        }
+        match action {
+            Some(Action::Theme) => self.theme = self.theme.next(),
+            Some(Action::Quit) => {
+                self.stop_solver();
+                self.quit = true
+            }
+            _ => {}
+        }
 
         match self.game_state {
             GameState::Prepare => {
-                self.event_prepare(pos, size, event)
+                self.event_prepare(event, action)
             }
             GameState::InProcess => {
-                self.event_progress(pos, size, event);
+                self.event_progress(pos, size, event, action);
+            }
+            GameState::GameOver => { self.event_game_over(event) }
+            GameState::Replay => { self.event_replay(event) }
+            GameState::LevelSelect => { self.event_level_select(event) }
+            GameState::Stats | GameState::Leaderboard => {
+                match event.press_args() {
+                    Some(Button::Keyboard(Key::S)) => self.toggle_screen(GameState::Stats),
+                    Some(Button::Keyboard(Key::L)) => self.toggle_screen(GameState::Leaderboard),
+                    _ => {}
+                }
             }
-            GameState::GameOver => { self.event_progress(pos, size, event) }
         }
     }
 
     //event-handler in progress game
-    fn event_progress<E: GenericEvent>(&mut self, pos: [f64; 2], size: f64, event: &E) {
-        if let Some(Button::Mouse(MouseButton::Left)) = event.press_args() {
+    fn event_progress<E: GenericEvent>(&mut self, pos: [f64; 2], size: f64, event: &E, action: Option<Action>) {
+        if let Some(action) = action {
+            self.act(action);
+        }
+        if self.paused {
+            return;
+        }
+        if let Some(args) = event.update_args() {
+            self.snap_back(args.dt);
+        }
+        if event.mouse_cursor_args().is_some() {
+            self.drag_to(size / self.gameboard.size as f64);
+        }
+        if event.release_args().is_some_and(|button| self.bindings.is_bound(Action::Select, button)) {
+            self.drop_tile();
+        }
+        if action == Some(Action::Select) {
+            //Release of the last drag was lost, so it was a click
+            if let Some(drag) = self.drag.take().filter(|d| !d.released) {
+                self.selected = Some(drag.cell);
+            }
             // Find coordinates relative to upper left corner.
             let x = self.cursor_pos[0] - pos[0];
             let y = self.cursor_pos[1] - pos[1];
             // Check that coordinates are inside board boundaries.
             if x >= 0.0 && x < size && y >= 0.0 && y < size {
                 // Compute the cell position.
-                let cell_x = (x / size * 4.0) as usize;
-                let cell_y = (y / size * 4.0) as usize;
-                //dbg!("x:{} y:{}",cell_x,cell_y);
-                match self.selected {
-                    Some(sel) => {
-                        self.swap_rectangle_or_cancel([cell_x, cell_y], [sel[0], sel[1]]);
+                let cells = self.gameboard.size as f64;
+                let cell = Position::new((y / size * cells) as usize, (x / size * cells) as usize);
+                //dbg!("cell:{:?}",cell);
+                match (self.selected, self.gameboard.zero_next_to(cell)) {
+                    (Some(sel), _) => {
+                        self.swap_rectangle_or_cancel(cell, sel);
+                    }
+                    (None, Some(zero)) if self.animator.is_over() && self.drag.is_none() => {
+                        //Tile across the edge of torus isn't dragged, it slides by click
+                        if self.gameboard.topology.is_wrap(cell, zero, self.gameboard.size) {
+                            if self.animate_swap(cell, zero) {
+                                self.moved(zero, self.animate_direction.opposite());
+                            }
+                        } else if self.is_allowed(cell) {
+                            self.drag = Some(Drag { cell, zero, start: self.cursor_pos, offset: 0.0, released: false });
+                        }
                     }
-                    None => {
-                        if self.gameboard.zero() != [cell_x, cell_y] {
-                            self.selected = Some([cell_x, cell_y])
+                    (None, _) => {
+                        if self.gameboard.is_tile(cell) {
+                            self.selected = Some(cell)
                         }
                     }
                 }
             }
         }
-        if let Some(Button::Mouse(MouseButton::Right)) = event.press_args() {
-            //Unselect
-            self.selected = None;
+        match event.press_args() {
+            Some(Button::Keyboard(Key::F5)) => self.save(),
+            Some(Button::Keyboard(Key::F1)) if self.tutorial.is_some() => self.end_tutorial(),
+            _ => {}
         }
     }
 
     //event-handler in prepare
-    fn event_prepare<E: GenericEvent>(&mut self, pos: [f64; 2], size: f64, event: &E) {
-        if let Some(Button::Keyboard(Key::Space)) = event.press_args() {
-            let x = self.cursor_pos[0] - pos[0];
-            let y = self.cursor_pos[1] - pos[1];
-            // Check that coordinates are inside board boundaries.
-            if x >= 0.0 && x < size && y >= 0.0 && y < size {}
+    fn event_prepare<E: GenericEvent>(&mut self, event: &E, action: Option<Action>) {
+        if action == Some(Action::Start) {
+            self.start_solver();
             self.game_state = InProcess;
+            return;
+        }
+        match event.press_args() {
+            Some(Button::Keyboard(Key::G)) => self.set_goal(self.gameboard.goal.next()),
+            Some(Button::Keyboard(Key::S)) => self.toggle_screen(GameState::Stats),
+            Some(Button::Keyboard(Key::L)) => self.toggle_screen(GameState::Leaderboard),
+            Some(Button::Keyboard(Key::P)) if !self.packs.is_empty() => self.game_state = GameState::LevelSelect,
+            Some(Button::Keyboard(Key::F1)) => self.start_tutorial(),
+            _ => {}
+        }
+    }
+
+    //event-handler on level select screen
+    fn event_level_select<E: GenericEvent>(&mut self, event: &E) {
+        let (pack, level) = self.level_cursor;
+        match event.press_args() {
+            Some(Button::Keyboard(Key::Up)) => self.level_cursor.1 = level.saturating_sub(1),
+            Some(Button::Keyboard(Key::Down)) => {
+                self.level_cursor.1 = (level + 1).min(self.packs[pack].levels.len().saturating_sub(1))
+            }
+            Some(Button::Keyboard(Key::Left)) => self.level_cursor = (pack.saturating_sub(1), 0),
+            Some(Button::Keyboard(Key::Right)) => self.level_cursor = ((pack + 1).min(self.packs.len() - 1), 0),
+            Some(Button::Keyboard(Key::Return)) if self.is_unlocked(pack, level) => {
+                if let Err(e) = self.play_level(pack, level) {
+                    println!("level can't be played: {}", e);
+                }
+            }
+            Some(Button::Keyboard(Key::P)) => {
+                self.game_state = if self.level.is_some() { GameOver } else { GameState::Prepare }
+            }
+            _ => {}
+        }
+    }
+
+    ///Change goal layout before the game, the board is made solvable for it
+    pub fn set_goal(&mut self, goal: GoalLayout) {
+        self.gameboard = match &self.replay.origin {
+            Origin::Seed(seed) => Gameboard::generate(*seed, self.replay.size, goal, self.replay.difficulty),
+            Origin::Board(board) => {
+                let mut board = Gameboard { goal, ..board.clone() };
+                board.make_solvable();
+                board
+            }
+        };
+        if let Origin::Board(_) = self.replay.origin {
+            self.replay.origin = Origin::Board(self.gameboard.clone());
+        }
+        self.replay.goal = goal;
+    }
+
+    //event-handler after win
+    fn event_game_over<E: GenericEvent>(&mut self, event: &E) {
+        if let Some(name) = self.name_input.as_mut() {
+            //Typing name, other keys are not commands now
+            if let Some(text) = event.text_args() {
+                for c in text.chars().filter(|c| !c.is_control()) {
+                    if name.chars().count() < Leaderboard::MAX_NAME {
+                        name.push(c);
+                    }
+                }
+            }
+            match event.press_args() {
+                Some(Button::Keyboard(Key::Backspace)) => { name.pop(); }
+                Some(Button::Keyboard(Key::Return)) => self.submit_name(),
+                _ => {}
+            }
+            return;
+        }
+        match event.press_args() {
+            Some(Button::Keyboard(Key::S)) => self.toggle_screen(GameState::Stats),
+            Some(Button::Keyboard(Key::L)) => self.toggle_screen(GameState::Leaderboard),
+            Some(Button::Keyboard(Key::P)) if !self.packs.is_empty() => self.game_state = GameState::LevelSelect,
+            Some(Button::Keyboard(Key::F12)) => self.screenshot = true,
+            _ => {}
+        }
+        if let Some(Button::Keyboard(Key::R)) = event.press_args() {
+            //Play saved file, so the record on disk is checked too
+            let replay = match &self.replay_path {
+                Some(path) => Replay::load(path).unwrap_or_else(|e| {
+                    println!("{}", e);
+                    self.replay.clone()
+                }),
+                None => self.replay.clone()
+            };
+            self.play(replay);
         }
     }
+
+    //event-handler in replay playback
+    fn event_replay<E: GenericEvent>(&mut self, event: &E) {
+        let mut player = match self.player.take() {
+            Some(player) => player,
+            None => return
+        };
+        if let Some(args) = event.update_args() {
+            player.tick(args.dt);
+            if self.animator.is_over() {
+                if let Some(m) = player.next_due() {
+                    let zero = self.gameboard.zeros().get(m.blank).copied();
+                    let legal = zero
+                        .and_then(|zero| self.gameboard.neighbour(zero, m.direction).map(|cell| (cell, zero)))
+                        .is_some_and(|(cell, zero)| self.animate_swap(cell, zero));
+                    if !legal {
+                        println!("illegal move #{} in replay", player.position);
+                        player.position = player.replay.moves.len();
+                    }
+                }
+                if player.is_finished() && player.verified.is_none() {
+                    player.verified = Some(self.gameboard.is_over());
+                }
+            }
+        }
+        let seek = match event.press_args() {
+            Some(Button::Keyboard(Key::Space)) => { player.toggle_pause(); None }
+            Some(Button::Keyboard(Key::Up)) => { player.faster(); None }
+            Some(Button::Keyboard(Key::Down)) => { player.slower(); None }
+            Some(Button::Keyboard(Key::Left)) => Some(player.position.saturating_sub(1)),
+            Some(Button::Keyboard(Key::Right)) => Some(player.position + 1),
+            Some(Button::Keyboard(Key::Home)) => Some(0),
+            Some(Button::Keyboard(Key::End)) => Some(player.replay.moves.len()),
+            _ => None
+        };
+        if let Some(position) = seek {
+            match player.seek(position) {
+                Ok(board) => {
+                    self.gameboard = board;
+                    self.animator.stop();
+                }
+                Err(e) => println!("{}", e)
+            }
+        }
+        self.player = Some(player);
+    }
+}
+
+impl Drop for GameboardController {
+    //Window may be closed while the solver works, the finished game is recorded anyway
+    fn drop(&mut self) {
+        self.stop_solver();
+    }
+}
+
+///Direction from `from` to the next field `to` of `board`, across the edge on torus
+fn direction(board: &Gameboard, from: Position, to: Position) -> Direction {
+    board.topology.direction(from, to, board.size).unwrap_or(Top)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::animator;
+    use piston::input::{ButtonArgs, ButtonState, ControllerAxisArgs, ControllerButton, Event, Input, Loop, Motion,
+        MouseButton, UpdateArgs};
+
+    fn anima() -> Box<dyn Animator> {
+        Box::new(animator::PlainAnimator::new(100.0, 10.0))
+    }
+
+    fn controller(board: &str) -> GameboardController {
+        GameboardController::new(board.parse().unwrap(), anima())
+    }
+
+    fn button(state: ButtonState, button: Button) -> Event {
+        Event::Input(Input::Button(ButtonArgs { state, button, scancode: None }), None)
+    }
+
+    fn press(key: Key) -> Event {
+        button(ButtonState::Press, Button::Keyboard(key))
+    }
+
+    ///Gamepad button in SDL numbers
+    fn pad(number: u8) -> Event {
+        button(ButtonState::Press, Button::Controller(ControllerButton::new(0, number)))
+    }
+
+    fn mouse(state: ButtonState) -> Event {
+        button(state, Button::Mouse(MouseButton::Left))
+    }
+
+    fn cursor(x: f64, y: f64) -> Event {
+        Event::Input(Input::Move(Motion::MouseCursor([x, y])), None)
+    }
+
+    fn update(dt: f64) -> Event {
+        Event::Loop(Loop::Update(UpdateArgs { dt }))
+    }
+
+    ///Solver which works until it is canceled
+    fn endless_solver() -> Job<usize> {
+        Job::spawn(|cancel: &AtomicBool| {
+            while !cancel.load(Ordering::Relaxed) {
+                thread::sleep(std::time::Duration::from_millis(1));
+            }
+            None
+        })
+    }
+
+    ///Send updates until background searches are over
+    fn wait(controller: &mut GameboardController) {
+        for _ in 0..1000 {
+            if controller.solver.is_none() && controller.hint_search.is_none() {
+                return;
+            }
+            controller.event([0.0; 2], 90.0, &update(0.0));
+            thread::sleep(std::time::Duration::from_millis(5));
+        }
+        panic!("search isn't over");
+    }
 
     #[test]
     fn new_gameboard_controller_test_smoke() {
-        let anima: Box<dyn Animator> = Box::new(animator::PlainAnimator::new(100.0, 10.0));
-        let _gb = GameboardController::new(Gameboard::new(), anima);
+        let _gb = GameboardController::new(Gameboard::new(), anima());
+    }
+
+    #[test]
+    fn set_goal_test() {
+        let mut controller = GameboardController::from_seed(5, anima());
+        controller.set_goal(GoalLayout::Snake);
+        assert_eq!(controller.gameboard.goal, GoalLayout::Snake);
+        assert!(controller.gameboard.is_solvable());
+        assert_eq!(controller.replay.initial(), controller.gameboard);
+    }
+
+    #[test]
+    fn name_input_test() {
+        let mut controller = GameboardController::new(Gameboard::new(), anima());
+        controller.game_state = GameState::GameOver;
+        controller.name_input = Some("An".to_owned());
+        controller.event([0.0; 2], 100.0, &Event::Input(Input::Text("n s".to_owned()), None));
+        controller.event([0.0; 2], 100.0, &press(Key::Backspace));
+        assert_eq!(controller.name_input.as_deref(), Some("Ann "));
+        assert_eq!(controller.game_state, GameState::GameOver);
+        controller.event([0.0; 2], 100.0, &press(Key::Return));
+        assert_eq!(controller.name_input, None);
+        controller.event([0.0; 2], 100.0, &press(Key::L));
+        assert_eq!(controller.game_state, GameState::Leaderboard);
+        controller.event([0.0; 2], 100.0, &press(Key::S));
+        controller.event([0.0; 2], 100.0, &press(Key::S));
+        assert_eq!(controller.game_state, GameState::GameOver);
+    }
+
+    #[test]
+    fn from_replay_test() {
+        let mut replay = Replay::new(Origin::Seed(9));
+        replay.size = 3;
+        replay.difficulty = Difficulty::Easy;
+        let mut board = replay.initial();
+        let direction = Direction::ALL.iter().copied().find(|d| board.move_zero(*d)).unwrap();
+        replay.record(direction, 1500);
+        let controller = GameboardController::from_replay(replay, anima()).unwrap();
+        assert_eq!(controller.gameboard, board);
+        assert_eq!(controller.elapsed(), 1.5);
+        assert_eq!(controller.record().difficulty, "easy");
+    }
+
+    #[test]
+    fn level_select_test() {
+        let mut controller = GameboardController::new(Gameboard::new(), anima());
+        let level = |cells: &str| crate::pack::Level { name: "l".to_owned(), size: 3, cells: cells.to_owned(), par: None };
+        controller.packs.push(Pack { id: "p".to_owned(), name: "P".to_owned(), levels: vec![level("1 2 3/4 5 6/7 0 8"), level("1 2 3/4 5 6/0 7 8")] });
+        let path = std::env::temp_dir().join(format!("fifteen_controller_progress_{}.json", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        controller.progress = Some(Progress::load(&path).unwrap());
+        controller.event([0.0; 2], 90.0, &press(Key::P));
+        assert_eq!(controller.game_state, GameState::LevelSelect);
+        controller.event([0.0; 2], 90.0, &press(Key::Down));
+        controller.event([0.0; 2], 90.0, &press(Key::Down));
+        assert_eq!(controller.level_cursor, (0, 1));
+        controller.event([0.0; 2], 90.0, &press(Key::Return));
+        assert_eq!(controller.game_state, GameState::LevelSelect);
+        controller.event([0.0; 2], 90.0, &press(Key::Up));
+        controller.event([0.0; 2], 90.0, &press(Key::Return));
+        assert_eq!(controller.game_state, GameState::InProcess);
+        assert_eq!(controller.gameboard.size, 3);
+        //Solve the first level by clicks: select tile 8, then zero field
+        for e in &[cursor(75.0, 75.0), mouse(ButtonState::Press), cursor(45.0, 75.0), mouse(ButtonState::Press)] {
+            controller.event([0.0; 2], 90.0, e);
+        }
+        assert_eq!(controller.game_state, GameState::GameOver);
+        assert!(controller.is_completed(0, 0));
+        wait(&mut controller);
+        assert!(!controller.is_computing());
+        assert_eq!(controller.optimal, Some(1));
+        assert_eq!(controller.record().stars, Some(3));
+        //Par of the level is used before optimal length
+        controller.packs[0].levels[0].par = Some(3);
+        controller.gameboard.moves = 4;
+        assert_eq!(controller.stars(), Some(1));
+        assert!(controller.is_unlocked(0, 1));
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn drag_test() {
+        let mut controller = controller("1 2 3/4 5 6/7 0 8");
+        controller.game_state = GameState::InProcess;
+        //Tile 8 is dragged left to zero field: 10 pixels of 30 and back
+        for e in &[cursor(75.0, 75.0), mouse(ButtonState::Press), cursor(65.0, 90.0)] {
+            controller.event([0.0; 2], 90.0, e);
+        }
+        let drag = controller.drag.unwrap();
+        assert!((drag.offset - 1.0 / 3.0).abs() < 1e-9);
+        controller.event([0.0; 2], 90.0, &cursor(100.0, 75.0));
+        assert_eq!(controller.drag.unwrap().offset, 0.0);
+        controller.event([0.0; 2], 90.0, &cursor(65.0, 75.0));
+        controller.event([0.0; 2], 90.0, &mouse(ButtonState::Release));
+        assert!(controller.drag.unwrap().released);
+        assert_eq!(controller.gameboard.moves, 0);
+        controller.event([0.0; 2], 90.0, &update(0.1));
+        assert_eq!(controller.drag, None);
+        //Past the half way the move is done
+        for e in &[mouse(ButtonState::Press), cursor(40.0, 75.0), mouse(ButtonState::Release)] {
+            controller.event([0.0; 2], 90.0, e);
+        }
+        assert_eq!(controller.drag, None);
+        assert_eq!(controller.gameboard.moves, 1);
+        assert_eq!(controller.replay.moves[0].direction, Direction::Right);
+        assert_eq!(controller.game_state, GameState::GameOver);
+    }
+
+    #[test]
+    fn designer_test() {
+        let mut controller = controller("1 2 3/4 # 0/6 5 0");
+        controller.game_state = GameState::InProcess;
+        //Obstacle isn't selected
+        for e in &[cursor(45.0, 45.0), mouse(ButtonState::Press), mouse(ButtonState::Release)] {
+            controller.event([0.0; 2], 90.0, e);
+        }
+        assert_eq!(controller.selected, None);
+        //Tile 5 goes right to the second zero field
+        for e in &[cursor(45.0, 75.0), mouse(ButtonState::Press), cursor(75.0, 75.0), mouse(ButtonState::Release)] {
+            controller.event([0.0; 2], 90.0, e);
+        }
+        assert_eq!(controller.replay.moves[0].blank, 1);
+        controller.act(Action::Undo);
+        assert_eq!(controller.gameboard.get(Position::new(2, 1)), 5);
+        controller.animator.stop();
+        controller.act(Action::Redo);
+        assert_eq!(controller.gameboard.get(Position::new(2, 2)), 5);
+        controller.animator.stop();
+        //Then up to the first one, obstacle stays in the goal
+        for e in &[cursor(75.0, 75.0), mouse(ButtonState::Press), cursor(75.0, 45.0), mouse(ButtonState::Release)] {
+            controller.event([0.0; 2], 90.0, e);
+        }
+        assert_eq!(controller.game_state, GameState::GameOver);
+        assert!(controller.replay.to_string().contains("moves=2LD;"));
+    }
+
+    #[test]
+    fn solver_test() {
+        let mut controller = controller("1 2 3/4 5 6/7 0 8");
+        let path = std::env::temp_dir().join(format!("fifteen_controller_stats_{}.json", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        controller.stats = Some(StatsStore::load(&path).unwrap());
+        controller.game_state = InProcess;
+        //Solver tells when it is canceled
+        let (sender, canceled) = mpsc::channel();
+        controller.solver = Some(Job::spawn(move |cancel: &AtomicBool| {
+            while !cancel.load(Ordering::Relaxed) {
+                thread::sleep(std::time::Duration::from_millis(1));
+            }
+            sender.send(()).unwrap();
+            None
+        }));
+        controller.act(Action::Move(Direction::Left));
+        controller.event([0.0; 2], 90.0, &update(0.1));
+        //Game over doesn't wait for the solver, the record does
+        assert_eq!(controller.game_state, GameOver);
+        assert!(controller.is_computing());
+        assert!(controller.stats.as_ref().unwrap().records.is_empty());
+        //Quit cancels the search and saves the record without optimal length
+        controller.event([0.0; 2], 90.0, &press(Key::Escape));
+        assert!(controller.quit);
+        assert!(canceled.recv_timeout(std::time::Duration::from_secs(5)).is_ok());
+        let records = &controller.stats.as_ref().unwrap().records;
+        assert_eq!((records.len(), records[0].optimal), (1, None));
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn drop_test() {
+        let mut controller = controller("1 2 3/4 5 6/7 0 8");
+        let path = std::env::temp_dir().join(format!("fifteen_controller_drop_{}.json", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        controller.stats = Some(StatsStore::load(&path).unwrap());
+        controller.game_state = InProcess;
+        controller.daily = Some(7);
+        controller.solver = Some(endless_solver());
+        controller.act(Action::Move(Direction::Left));
+        assert!(controller.is_computing());
+        //Window is closed without quit action
+        drop(controller);
+        let records = StatsStore::load(&path).unwrap().records;
+        assert_eq!((records.len(), records[0].daily), (1, Some(7)));
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn gamepad_test() {
+        let initial: Gameboard = "1 2 3/4 5 6/7 0 8".parse().unwrap();
+        let mut controller = controller("1 2 3/4 5 6/7 0 8");
+        controller.event([0.0; 2], 90.0, &pad(0));
+        assert_eq!(controller.game_state, GameState::InProcess);
+        //D-pad up: no tile under zero field
+        controller.event([0.0; 2], 90.0, &pad(11));
+        assert_eq!(controller.gameboard.moves, 0);
+        //D-pad down: tile 5 goes down, then B takes it back
+        controller.event([0.0; 2], 90.0, &pad(12));
+        assert_eq!(controller.replay.moves[0].direction, Direction::Top);
+        controller.animator.stop();
+        controller.event([0.0; 2], 90.0, &pad(1));
+        assert_eq!(controller.gameboard, initial);
+        assert!(controller.replay.moves.is_empty());
+        //Right shoulder does it again, then it is taken back once more
+        controller.animator.stop();
+        controller.event([0.0; 2], 90.0, &pad(10));
+        assert_eq!(controller.gameboard.moves, 1);
+        controller.animator.stop();
+        controller.event([0.0; 2], 90.0, &pad(1));
+        controller.animator.stop();
+        //Y restarts after a move
+        controller.event([0.0; 2], 90.0, &pad(14));
+        controller.event([0.0; 2], 90.0, &pad(3));
+        assert_eq!(controller.gameboard, initial);
+        //X selects tile 8 when the search is over
+        controller.event([0.0; 2], 90.0, &pad(2));
+        wait(&mut controller);
+        assert_eq!(controller.selected, Some(Position::new(2, 2)));
+        assert_eq!(controller.hints, 1);
+        //Back switches overlay of placed tiles
+        controller.event([0.0; 2], 90.0, &pad(4));
+        assert!(controller.overlay);
+        //Start pauses: clock and moves wait
+        controller.event([0.0; 2], 90.0, &pad(6));
+        controller.event([0.0; 2], 90.0, &pad(13));
+        controller.event([0.0; 2], 90.0, &update(1.0));
+        assert_eq!(controller.gameboard.moves, 0);
+        assert_eq!(controller.elapsed(), 0.0);
+        controller.event([0.0; 2], 90.0, &pad(6));
+        //Stick left slides tile 8 to zero field
+        let stick = Event::Input(Input::Move(Motion::ControllerAxis(ControllerAxisArgs::new(0, 0, -1.0))), None);
+        controller.event([0.0; 2], 90.0, &stick);
+        assert_eq!(controller.game_state, GameState::GameOver);
+        //Theme and quit work on any screen
+        controller.event([0.0; 2], 90.0, &press(Key::T));
+        assert_eq!(controller.theme, crate::gameboard_view::Theme::Dark);
+        controller.event([0.0; 2], 90.0, &press(Key::Escape));
+        assert!(controller.quit);
     }
 
     #[test]
     fn change_direction_test(){
-        let anima: Box<dyn Animator> = Box::new(animator::PlainAnimator::new(100.0, 10.0));
-        let _gb = GameboardController::new(Gameboard::new(), anima);
+        let _gb = GameboardController::new(Gameboard::new(), anima());
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn tutorial_test() {
+        use crate::tutorial::LESSONS;
+        let mut controller = GameboardController::from_seed(5, anima());
+        let initial = controller.gameboard.clone();
+        controller.event([0.0; 2], 90.0, &press(Key::F1));
+        assert_eq!(controller.game_state, GameState::InProcess);
+        assert_eq!(controller.gameboard, LESSONS[0].board.parse().unwrap());
+        //Zero goes around to the corner, 1 and 2 slide in
+        for zero in [Top, Top, Left, Top, Right, Right] {
+            controller.act(Action::Move(zero.opposite()));
+            controller.animator.stop();
+        }
+        assert_eq!(controller.tutorial.as_ref().unwrap().step, 1);
+        //2 is placed, it can't go back
+        controller.act(Action::Move(Right));
+        assert_eq!(controller.gameboard.moves, 6);
+        assert!(controller.tutorial.as_ref().unwrap().feedback.is_some());
+
+        //Solved board finishes the lesson, not the game
+        controller.gameboard = "1 2 3 4/5 6 7 8/9 10 11 12/13 14 0 15".parse().unwrap();
+        controller.act(Action::Move(Left));
+        assert_eq!(controller.tutorial.as_ref().unwrap().lesson, 1);
+        assert_eq!(controller.gameboard, LESSONS[1].board.parse().unwrap());
+        assert_eq!(controller.game_state, GameState::InProcess);
+        //Hint follows the lesson and tells its sub-goal
+        controller.act(Action::Hint);
+        assert_eq!(controller.explanation.map(|g| g.to_string()).as_deref(), Some("column 1: place 5"));
+        assert!(controller.selected.is_some());
+
+        controller.event([0.0; 2], 90.0, &press(Key::F1));
+        assert_eq!(controller.tutorial, None);
+        assert_eq!(controller.game_state, GameState::Prepare);
+        assert_eq!(controller.gameboard, initial);
+    }
+}
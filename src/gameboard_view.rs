@@ -1,14 +1,27 @@
-use graphics::{Context, Graphics, Image, text, Transformed};
+use graphics::{Context, Graphics, text, Transformed};
+use graphics::triangulation;
 use graphics::character::CharacterCache;
-use graphics::types::Color;
-use graphics::{Line, Rectangle};
+use graphics::types::{self, Color};
+use graphics::Rectangle;
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::Path;
+use image::RgbaImage;
 use crate::gameboard_controller::GameState;
+use piston::input::{Button, GenericEvent, Key, MouseButton};
 
-use super::gameboard::{FSIZE, SIZE};
-use super::gameboard_controller::GameboardController;
+use super::gameboard::Position;
+use super::gameboard_controller::{Drag, GameboardController};
 use crate::animator::Direction;
+use crate::bindings::Action;
+use crate::daily;
+use crate::stats;
+use crate::tutorial;
+use crate::leaderboard::{Leaderboard, Ranking};
+use crate::software::{SoftwareGraphics, SoftwareTexture};
 
-#[derive(Default)]
+#[derive(Default, Clone)]
 ///Rendering settings
 pub struct GameboardViewSettings {
     pub position: [f64; 2],
@@ -27,6 +40,20 @@ pub struct GameboardViewSettings {
     /// Edge radius between cells.
     pub cell_edge_radius: f64,
     pub text_color: Color,
+    ///Background of messages over the board
+    pub panel_color: Color,
+    ///Bars of statistics histogram and of progress
+    pub chart_color: Color,
+    ///Overlay over tiles on their goal places
+    pub placed_color: Color,
+    ///Overlay dimming tiles out of their places
+    pub misplaced_color: Color,
+    ///Placed tiles and progress are shown, key of the overlay action switches it
+    pub overlay: bool,
+    ///Frame of tiles to place in current tutorial step
+    pub target_color: Color,
+    ///Immovable cells of designed boards
+    pub obstacle_color: Color,
 }
 
 impl GameboardViewSettings {
@@ -42,17 +69,228 @@ impl GameboardViewSettings {
             board_edge_radius: 1.0,
             cell_edge_radius: 1.0,
             text_color: [0.0, 0.0, 0.0, 1.0],
+            panel_color: [1.0, 1.0, 1.0, 0.85],
+            chart_color: [0.4, 0.4, 0.9, 1.0],
+            placed_color: [0.3, 0.8, 0.3, 0.45],
+            misplaced_color: [0.0, 0.0, 0.0, 0.12],
+            overlay: false,
+            target_color: [0.9, 0.45, 0.1, 1.0],
+            obstacle_color: [0.3, 0.3, 0.4, 1.0],
         }
     }
+
+    ///Width and height of the window showing the board
+    pub fn window_size(&self) -> [f64; 2] {
+        [self.size + 2.0 * self.position[0], self.size + self.position[1] + 10.0]
+    }
+
+    ///Settings with colors of `theme`
+    pub fn with_theme(theme: Theme) -> Self {
+        let settings = GameboardViewSettings::new();
+        match theme {
+            Theme::Light => settings,
+            Theme::Learning => GameboardViewSettings { overlay: true, ..settings },
+            Theme::Dark => GameboardViewSettings {
+                background_color: [0.15, 0.15, 0.2, 1.0],
+                zero_color: [0.05, 0.05, 0.08, 1.0],
+                selected_color: [0.45, 0.4, 0.1, 1.0],
+                border_color: [0.7, 0.7, 0.8, 1.0],
+                between_color: [0.35, 0.35, 0.4, 1.0],
+                text_color: [0.9, 0.9, 0.9, 1.0],
+                panel_color: [0.1, 0.1, 0.1, 0.85],
+                chart_color: [0.5, 0.5, 1.0, 1.0],
+                placed_color: [0.2, 0.6, 0.25, 0.5],
+                misplaced_color: [0.0, 0.0, 0.0, 0.35],
+                target_color: [1.0, 0.6, 0.2, 1.0],
+                obstacle_color: [0.5, 0.5, 0.55, 1.0],
+                ..settings
+            },
+        }
+    }
+}
+
+///Color set of the settings
+#[derive(Debug, Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Hash, Default)]
+pub enum Theme {
+    #[default]
+    Light,
+    Dark,
+    ///Light colors, placed tiles and progress are shown from the start
+    Learning,
+}
+
+impl Theme {
+    pub const ALL: [Theme; 3] = [Theme::Light, Theme::Dark, Theme::Learning];
+
+    ///Theme after this one, the last is followed by the first
+    pub fn next(self) -> Theme {
+        let i = Theme::ALL.iter().position(|t| *t == self).unwrap_or(0);
+        Theme::ALL[(i + 1) % Theme::ALL.len()]
+    }
+}
+
+names!(Theme, "theme", {
+    Light => "light",
+    Dark => "dark",
+    Learning => "learning",
+});
+
+///Zoom of one wheel step or key press
+const ZOOM_STEP: f64 = 1.25;
+
+///Part of the board shown when it is zoomed in, zoom 1 shows the whole board
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Viewport {
+    pub zoom: f64,
+    ///Top left corner of the shown part in pixels of the zoomed board
+    pub offset: [f64; 2],
+}
+
+impl Default for Viewport {
+    fn default() -> Self {
+        Viewport { zoom: 1.0, offset: [0.0; 2] }
+    }
 }
 
 pub struct GameboardView {
-    pub settings: GameboardViewSettings
+    pub settings: GameboardViewSettings,
+    pub viewport: Viewport,
+    cursor: [f64; 2],
+    ///Last cursor of the middle button drag
+    pan: Option<[f64; 2]>,
+    ///Zero field seen last, the viewport follows it when it moves
+    zero: Position,
 }
 
 impl GameboardView {
     pub fn new(settings: GameboardViewSettings) -> Self {
-        GameboardView { settings }
+        GameboardView { settings, viewport: Viewport::default(), cursor: [0.0; 2], pan: None, zero: Position::default() }
+    }
+
+    ///Biggest zoom, cells are as big as on 4x4 board
+    fn max_zoom(board_size: usize) -> f64 {
+        (board_size as f64 / 4.0).max(1.0)
+    }
+
+    ///Position and side of the whole board in the window after zoom, for the mouse of the controller
+    pub fn board_area(&self) -> ([f64; 2], f64) {
+        let position = self.settings.position;
+        let offset = self.viewport.offset;
+        ([position[0] - offset[0], position[1] - offset[1]], self.settings.size * self.viewport.zoom)
+    }
+
+    ///Zoom by `factor` keeping point `at` of the window on its place
+    pub fn zoom(&mut self, factor: f64, at: [f64; 2], board_size: usize) {
+        let zoom = (self.viewport.zoom * factor).clamp(1.0, Self::max_zoom(board_size));
+        let ratio = zoom / self.viewport.zoom;
+        for i in 0..2 {
+            let at = at[i] - self.settings.position[i];
+            self.viewport.offset[i] = (at + self.viewport.offset[i]) * ratio - at;
+        }
+        self.viewport.zoom = zoom;
+        self.pan_by([0.0; 2]);
+    }
+
+    ///Move shown part by `shift` pixels, it stays on the board
+    pub fn pan_by(&mut self, shift: [f64; 2]) {
+        let max = self.settings.size * (self.viewport.zoom - 1.0);
+        for (offset, shift) in self.viewport.offset.iter_mut().zip(shift) {
+            *offset = (*offset + shift).clamp(0.0, max);
+        }
+    }
+
+    ///Move shown part the least so `cell` is in it
+    fn follow(&mut self, cell: Position, board_size: usize) {
+        let cell_size = self.settings.size * self.viewport.zoom / board_size as f64;
+        for (i, index) in [cell.col, cell.row].iter().enumerate() {
+            let (start, end) = (*index as f64 * cell_size, (*index + 1) as f64 * cell_size);
+            let offset = &mut self.viewport.offset[i];
+            if start < *offset {
+                *offset = start;
+            } else if end > *offset + self.settings.size {
+                *offset = end - self.settings.size;
+            }
+        }
+    }
+
+    ///Zoom by mouse wheel and +/- keys, pan by middle button drag on screens with the board.
+    ///False for mouse presses out of the shown part of zoomed board, they aren't for hidden cells
+    pub fn event<E: GenericEvent>(&mut self, controller: &GameboardController, event: &E) -> bool {
+        let board_size = controller.gameboard.size;
+        if let Some(cursor) = event.mouse_cursor_args() {
+            if let Some(last) = self.pan {
+                self.pan_by([last[0] - cursor[0], last[1] - cursor[1]]);
+                self.pan = Some(cursor);
+            }
+            self.cursor = cursor;
+        }
+        if !matches!(controller.game_state, GameState::InProcess | GameState::GameOver | GameState::Replay) {
+            self.pan = None;
+            return true;
+        }
+        //Board of another size may allow less zoom
+        self.zoom(1.0, self.settings.position, board_size);
+        let center = [self.settings.position[0] + self.settings.size / 2.0, self.settings.position[1] + self.settings.size / 2.0];
+        if let Some([_, scroll]) = event.mouse_scroll_args() {
+            self.zoom(ZOOM_STEP.powf(scroll), self.cursor, board_size);
+        }
+        match event.press_args() {
+            Some(Button::Keyboard(Key::Equals)) | Some(Button::Keyboard(Key::NumPadPlus)) => self.zoom(ZOOM_STEP, center, board_size),
+            Some(Button::Keyboard(Key::Minus)) | Some(Button::Keyboard(Key::NumPadMinus)) => self.zoom(1.0 / ZOOM_STEP, center, board_size),
+            Some(Button::Mouse(MouseButton::Middle)) => self.pan = Some(self.cursor),
+            _ => {}
+        }
+        if event.release_args() == Some(Button::Mouse(MouseButton::Middle)) {
+            self.pan = None;
+        }
+        let zero = controller.gameboard.zero();
+        if zero != self.zero {
+            self.zero = zero;
+            self.follow(zero, board_size);
+        }
+        let [x, y] = [self.cursor[0] - self.settings.position[0], self.cursor[1] - self.settings.position[1]];
+        let shown = (0.0..self.settings.size).contains(&x) && (0.0..self.settings.size).contains(&y);
+        shown || self.viewport.zoom == 1.0 || !matches!(event.press_args(), Some(Button::Mouse(_)))
+    }
+
+    ///View of the whole zoomed board and context clipped to its shown part
+    fn zoomed(&self, c: &Context) -> (GameboardView, Context) {
+        let (position, size) = self.board_area();
+        let view = GameboardView::new(GameboardViewSettings { position, size, ..self.settings.clone() });
+        (view, self.clipped(c))
+    }
+
+    ///Context drawing only on the board, inside the clip of `c` if it has one
+    fn clipped(&self, c: &Context) -> Context {
+        //Scissor is in pixels of the window, they may be smaller than points
+        let scale = c.viewport.map_or(1.0, |v| v.draw_size[0] as f64 / v.window_size[0]);
+        let settings = &self.settings;
+        let mut rect = [settings.position[0], settings.position[1], settings.position[0] + settings.size, settings.position[1] + settings.size]
+            .map(|v| (v * scale).round().max(0.0));
+        if let Some([x, y, w, h]) = c.draw_state.scissor {
+            let clip = [x, y, x + w, y + h].map(|v| v as f64);
+            rect = [rect[0].max(clip[0]), rect[1].max(clip[1]), rect[2].min(clip[2]), rect[3].min(clip[3])];
+        }
+        let [x, y, right, bottom] = rect.map(|v| v as u32);
+        Context { draw_state: c.draw_state.scissor([x, y, right.saturating_sub(x), bottom.saturating_sub(y)]), ..*c }
+    }
+
+    ///Draw current screen into image of the window size, without window
+    pub fn render<C: CharacterCache<Texture=SoftwareTexture>>(&self, controller: &mut GameboardController, glyphs: &mut C) -> RgbaImage {
+        let [width, height] = self.settings.window_size();
+        let mut g = SoftwareGraphics::new(width as u32, height as u32);
+        let c = Context::new_abs(width, height);
+        g.clear_color([1.0; 4]);
+        self.draw(controller, glyphs, &c, &mut g);
+        g.image
+    }
+
+    ///Save picture of current screen
+    pub fn export_png<C: CharacterCache<Texture=SoftwareTexture>, P: AsRef<Path>>(&self, controller: &mut GameboardController, glyphs: &mut C, path: P) -> io::Result<()> {
+        if let Some(dir) = path.as_ref().parent() {
+            fs::create_dir_all(dir)?;
+        }
+        self.render(controller, glyphs).save(path)
     }
 
     /// Draw gameboard.
@@ -60,12 +298,185 @@ impl GameboardView {
         match controller.game_state {
             GameState::Prepare => { self.draw_prepare(controller, glyphs, c, g) }
             GameState::InProcess => { self.draw_progress(controller, glyphs, c, g) }
-            GameState::GameOver => {}
+            GameState::GameOver => { self.draw_game_over(controller, glyphs, c, g) }
+            GameState::Replay => { self.draw_replay(controller, glyphs, c, g) }
+            GameState::Stats => { self.draw_stats(controller, glyphs, c, g) }
+            GameState::Leaderboard => { self.draw_leaderboard(controller, glyphs, c, g) }
+            GameState::LevelSelect => { self.draw_level_select(controller, glyphs, c, g) }
+        };
+    }
+
+    ///Draw solved board and how to watch it again
+    fn draw_game_over<G: Graphics, C: CharacterCache<Texture=G::Texture>>(&self, controller: &mut GameboardController, glyphs: &mut C, c: &Context, g: &mut G) {
+        let settings = &self.settings;
+        self.draw_board(c, g);
+        self.draw_moving(controller, glyphs, c, g);
+
+        let panel = [
+            settings.position[0] + 20.0, settings.position[1] + settings.size / 4.0,
+            settings.size - 40.0, settings.size / 2.0,
+        ];
+        Rectangle::new(settings.panel_color).draw(panel, &c.draw_state, c.transform, g);
+
+        let mut lines = vec![
+            format!("Solved in {} moves, {}", controller.gameboard.moves, stats::format_time(controller.elapsed())),
+        ];
+        if let Some(best) = controller.stats.as_ref().and_then(|s| s.best(controller.gameboard.size, &controller.difficulty.to_string())) {
+            lines.push(format!("Best: {} moves, {}", best.moves, stats::format_time(best.time)));
+        }
+        if let Some(stars) = controller.stars() {
+            let par = controller.level.and_then(|(pack, level)| controller.packs[pack].levels[level].par);
+            let reference = if par.is_some() { "par" } else { "optimal" };
+            let stars = "*".repeat(stars as usize) + &"-".repeat((stats::MAX_STARS - stars) as usize);
+            lines.push(format!("Stars: {} ({} {})", stars, reference, controller.reference().unwrap_or(0)));
+        } else if controller.is_computing() {
+            lines.push("Stars: computing optimal...".to_owned());
+        }
+        if let (Some(store), Some(_)) = (&controller.stats, controller.daily) {
+            let (streak, best) = store.daily_streak(stats::today());
+            lines.push(format!("Daily streak: {} days, best {}", streak, best));
+        }
+        match &controller.name_input {
+            Some(name) => lines.push(format!("Name: {}_ (Enter)", name)),
+            None if controller.level.is_some() => lines.push("R - replay, P - levels, S - stats".to_owned()),
+            None => lines.push("R - replay, S - stats, L - top".to_owned()),
+        }
+        for (i, line) in lines.iter().enumerate() {
+            self.draw_text(line, 22, [panel[0] + 15.0, panel[1] + 50.0 + i as f64 * 40.0], glyphs, c, g);
+        }
+    }
+
+    ///Draw averages, histogram of moves and streaks of all games
+    fn draw_stats<G: Graphics, C: CharacterCache<Texture=G::Texture>>(&self, controller: &GameboardController, glyphs: &mut C, c: &Context, g: &mut G) {
+        let settings = &self.settings;
+        self.draw_board(c, g);
+        self.draw_text("Statistics (S - back)", 24, [settings.position[0], settings.position[1] - 15.0], glyphs, c, g);
+        let summary = match &controller.stats {
+            Some(store) => store.summary(stats::today()),
+            None => {
+                self.draw_text("Statistics are not available", 22, [settings.position[0] + 15.0, settings.position[1] + 40.0], glyphs, c, g);
+                return;
+            }
+        };
+        let lines = [
+            format!("Games: {}", summary.games),
+            format!("Average: {:.0} moves, {}", summary.average_moves, stats::format_time(summary.average_time)),
+            format!("Streak: {} days, best {}", summary.current_streak, summary.best_streak),
+        ];
+        for (i, line) in lines.iter().enumerate() {
+            self.draw_text(line, 22, [settings.position[0] + 15.0, settings.position[1] + 40.0 + i as f64 * 35.0], glyphs, c, g);
+        }
+
+        //Histogram of moves, bars are scaled to the biggest bucket
+        let chart = [settings.position[0] + 15.0, settings.position[1] + 160.0, settings.size - 30.0, settings.size - 200.0];
+        let max = summary.histogram.iter().copied().max().unwrap_or(0).max(1) as f64;
+        let width = chart[2] / summary.histogram.len().max(1) as f64;
+        for (i, count) in summary.histogram.iter().enumerate() {
+            let height = (chart[3] - 20.0) * *count as f64 / max;
+            let bar = [chart[0] + i as f64 * width + 2.0, chart[1] + chart[3] - 20.0 - height, width - 4.0, height];
+            Rectangle::new(settings.chart_color).draw(bar, &c.draw_state, c.transform, g);
+            if i % 2 == 0 || summary.histogram.len() < 8 {
+                let label = (i * stats::StatsStore::HISTOGRAM_BUCKET).to_string();
+                self.draw_text(&label, 12, [chart[0] + i as f64 * width + 2.0, chart[1] + chart[3]], glyphs, c, g);
+            }
+        }
+    }
+
+    ///Draw best scores for current board size by moves and by time
+    fn draw_leaderboard<G: Graphics, C: CharacterCache<Texture=G::Texture>>(&self, controller: &GameboardController, glyphs: &mut C, c: &Context, g: &mut G) {
+        let settings = &self.settings;
+        self.draw_board(c, g);
+        self.draw_text("Top scores (L - back)", 24, [settings.position[0], settings.position[1] - 15.0], glyphs, c, g);
+        let leaderboard = match &controller.leaderboard {
+            Some(leaderboard) => leaderboard,
+            None => {
+                self.draw_text("Leaderboard is not available", 22, [settings.position[0] + 15.0, settings.position[1] + 40.0], glyphs, c, g);
+                return;
+            }
+        };
+        let tables = [("Fewest moves", Ranking::FewestMoves), ("Fastest", Ranking::Fastest)];
+        for (t, (title, ranking)) in tables.iter().enumerate() {
+            let top = settings.position[1] + 40.0 + t as f64 * settings.size / 2.0;
+            self.draw_text(title, 22, [settings.position[0] + 15.0, top], glyphs, c, g);
+            for (i, score) in leaderboard.top(controller.gameboard.size, *ranking, Leaderboard::TOP).iter().enumerate() {
+                let line = format!("{}. {} - {} moves, {}", i + 1, score.name, score.moves, stats::format_time(score.time));
+                self.draw_text(&line, 18, [settings.position[0] + 25.0, top + 30.0 + i as f64 * 30.0], glyphs, c, g);
+            }
+        }
+    }
+
+    ///Draw levels of the pack under cursor: solved, open and locked
+    fn draw_level_select<G: Graphics, C: CharacterCache<Texture=G::Texture>>(&self, controller: &GameboardController, glyphs: &mut C, c: &Context, g: &mut G) {
+        const LINES: usize = 10;
+        let settings = &self.settings;
+        self.draw_board(c, g);
+        let (pack_index, cursor) = controller.level_cursor;
+        let pack = &controller.packs[pack_index];
+        let title = format!("{} ({}/{})", pack.name, pack_index + 1, controller.packs.len());
+        self.draw_text(&title, 24, [settings.position[0], settings.position[1] - 15.0], glyphs, c, g);
+
+        //Cursor is kept on the screen when pack has more levels than lines
+        let first = cursor.saturating_sub(LINES - 1);
+        let line_height = (settings.size - 40.0) / LINES as f64;
+        for (i, level) in pack.levels.iter().enumerate().skip(first).take(LINES) {
+            let top = settings.position[1] + (i - first) as f64 * line_height;
+            if i == cursor {
+                let rect = [settings.position[0], top, settings.size, line_height];
+                Rectangle::new(settings.selected_color).draw(rect, &c.draw_state, c.transform, g);
+            }
+            let mark = if controller.is_completed(pack_index, i) {
+                "[x]"
+            } else if controller.is_unlocked(pack_index, i) {
+                "[ ]"
+            } else {
+                "[locked]"
+            };
+            let mut line = format!("{} {}. {} {}x{}", mark, i + 1, level.name, level.size, level.size);
+            if let Some(par) = level.par {
+                line += &format!(", par {}", par);
+            }
+            self.draw_text(&line, 20, [settings.position[0] + 10.0, top + line_height * 0.7], glyphs, c, g);
+        }
+        let help = "Up/Down - level, Left/Right - pack, Enter - play, P - back";
+        self.draw_text(help, 14, [settings.position[0] + 10.0, settings.position[1] + settings.size - 12.0], glyphs, c, g);
+    }
+
+    fn draw_text<G: Graphics, C: CharacterCache<Texture=G::Texture>>(&self, line: &str, font_size: u32, position: [f64; 2], glyphs: &mut C, c: &Context, g: &mut G) {
+        text::Text::new_color(self.settings.text_color, font_size)
+            .draw(line,
+                  glyphs,
+                  &c.draw_state,
+                  c.transform.trans(position[0], position[1]),
+                  g).unwrap_or(());
+    }
+
+    ///Draw replay playback with its position, speed and result
+    fn draw_replay<G: Graphics, C: CharacterCache<Texture=G::Texture>>(&self, controller: &mut GameboardController, glyphs: &mut C, c: &Context, g: &mut G) {
+        let settings = &self.settings;
+        self.draw_board(c, g);
+        self.draw_moving(controller, glyphs, c, g);
+        let status = match &controller.player {
+            Some(player) => {
+                let state = match player.verified {
+                    Some(true) => "solved".to_owned(),
+                    Some(false) => "not solved!".to_owned(),
+                    None if player.paused => "paused".to_owned(),
+                    None => format!("x{}", player.speed),
+                };
+                format!("Replay {}/{} {}", player.position, player.replay.moves.len(), state)
+            }
+            None => "Replay".to_owned()
         };
+        text::Text::new_color(settings.text_color, 24)
+            .draw(&status,
+                  glyphs,
+                  &c.draw_state,
+                  c.transform.trans(settings.position[0], settings.position[1] - 15.0),
+                  g).unwrap_or(());
     }
 
     /// Draw prepare
-    fn draw_prepare<G: Graphics, C: CharacterCache<Texture=G::Texture>>(&self, _controller: &GameboardController, glyphs: &mut C, c: &Context, g: &mut G) {
+    fn draw_prepare<G: Graphics, C: CharacterCache<Texture=G::Texture>>(&self, controller: &GameboardController, glyphs: &mut C, c: &Context, g: &mut G) {
         let settings = &self.settings;
 
         //Board
@@ -77,68 +488,233 @@ impl GameboardView {
             .draw(board_rect, &c.draw_state, c.transform, g);
 
         //start Game
-        let points = "Press Space to start!";
+        let points = match controller.bindings.button_name(Action::Start) {
+            Some(button) => format!("Press {} to start!", button),
+            None => "Start isn't bound".to_owned(),
+        };
         text::Text::new_color(settings.text_color, 40)
-            .draw(points,
+            .draw(&points,
+                  glyphs,
+                  &c.draw_state,
+                  c.transform.trans(10.0, settings.size * 0.375),
+                  g).unwrap_or(());
+
+        self.draw_text("F1 - tutorial", 24, [10.0, settings.size * 0.5], glyphs, c, g);
+
+        //Goal layout of this game
+        let goal = format!("G - goal: {}, S - stats, L - top", controller.gameboard.goal);
+        text::Text::new_color(settings.text_color, 24)
+            .draw(&goal,
                   glyphs,
                   &c.draw_state,
-                  c.transform.trans(10.0, settings.size / FSIZE * 1.5),
-                  g).unwrap_or_else(|_| ());
+                  c.transform.trans(10.0, settings.size * 0.625),
+                  g).unwrap_or(());
+
+        if let Some(day) = controller.daily {
+            let mut line = format!("Daily challenge {}", daily::date(day));
+            if let Some(store) = &controller.stats {
+                if store.daily_completed(day) {
+                    line += ", solved";
+                }
+                line += &format!(", streak {}", store.daily_streak(stats::today()).0);
+            }
+            self.draw_text(&line, 24, [10.0, settings.size * 0.75], glyphs, c, g);
+        }
+
+        if !controller.packs.is_empty() {
+            self.draw_text("P - puzzle packs", 24, [10.0, settings.size * 0.875], glyphs, c, g);
+        }
     }
 
     ///Draw in progress
     fn draw_progress<G: Graphics, C: CharacterCache<Texture=G::Texture>>(&self, controller: &mut GameboardController, glyphs: &mut C, c: &Context, g: &mut G) {
-       // let animate_shift = controller.animator.animate(controller.animate_direction);
-        let animate_shift=[0.0;2];
         self.draw_board(c, g);
-        self.draw_fields(controller, animate_shift, c, g);
-        self.draw_lines(c, g);
-        self.draw_cells(controller, glyphs, animate_shift, c, g);
+        if controller.paused {
+            //Board is hidden, so paused clock doesn't give time to think
+            let settings = &self.settings;
+            let position = [settings.position[0] + settings.size * 0.3, settings.position[1] + settings.size * 0.5];
+            self.draw_text("Paused", 40, position, glyphs, c, g);
+            self.draw_points(controller, glyphs, c, g);
+            return;
+        }
+        self.draw_moving(controller, glyphs, c, g);
+        if controller.tutorial.is_some() {
+            self.draw_lesson(controller, glyphs, c, g);
+            return;
+        }
+        if self.settings.overlay != controller.overlay {
+            self.draw_progress_bar(controller, glyphs, c, g);
+        }
+        if let Some(goal) = controller.explanation {
+            self.draw_text(&format!("Hint: {}", goal), 12, [self.settings.position[0], self.settings.position[1] - 4.0], glyphs, c, g);
+        }
         self.draw_points(controller, glyphs, c, g);
     }
 
-    fn draw_cells<G: Graphics, C: CharacterCache<Texture=G::Texture>>(&self, controller: &mut GameboardController, glyphs: &mut C, animate_shift:[f64;2], c: &Context, g: &mut G) {
+    ///Fields, lines and cells with one step of the animation, so both of them are moved equally
+    fn draw_moving<G: Graphics, C: CharacterCache<Texture=G::Texture>>(&self, controller: &mut GameboardController, glyphs: &mut C, c: &Context, g: &mut G) {
+        if self.viewport.zoom > 1.0 {
+            let (view, c) = self.zoomed(c);
+            view.draw_moving(controller, glyphs, &c, g);
+            return;
+        }
+        let cell_size = self.settings.size / controller.gameboard.size as f64;
+        let animate_shift = self.animate_shift(controller, cell_size);
+        let board = &controller.gameboard;
+        let zero = controller.moving_zero();
+        if controller.animator.is_over() || !board.topology.is_wrap(controller.animate_cell, zero, board.size) {
+            self.draw_fields(controller, animate_shift, c, g);
+            self.draw_lines(board.size, c, g);
+            self.draw_cells(controller, glyphs, animate_shift, c, g);
+            return;
+        }
+        //Tile going across the edge of torus is seen at both edges: the board is drawn once more
+        //a board away on each side, only parts of the slide coming into the board are seen
+        let c = self.clipped(c);
+        let axis = if controller.animate_cell.row == zero.row { 0 } else { 1 };
+        let views: Vec<GameboardView> = [0.0, self.settings.size, -self.settings.size].iter()
+            .map(|shift| {
+                let mut position = self.settings.position;
+                position[axis] += shift;
+                GameboardView::new(GameboardViewSettings { position, ..self.settings.clone() })
+            })
+            .collect();
+        for view in &views {
+            view.draw_fields(controller, animate_shift, &c, g);
+        }
+        self.draw_lines(board.size, &c, g);
+        for view in &views {
+            view.draw_cells(controller, glyphs, animate_shift, &c, g);
+        }
+    }
+
+    fn draw_cells<G: Graphics, C: CharacterCache<Texture=G::Texture>>(&self, controller: &GameboardController, glyphs: &mut C, animate_shift: [f64; 2], c: &Context, g: &mut G) {
         let settings = &self.settings;
         //Draw cells characters
-        let animate_shift = controller.animator.animate(controller.animate_direction);
-        let cell_size = settings.size / FSIZE;
-        for j in 0..SIZE {
-            for i in 0..SIZE {
-                let ch = controller.gameboard.cell_as_string([i, j]);
-                let position_in_cell = [
-                    settings.position[0] + i as f64 * cell_size + 30.0,
-                    settings.position[1] + j as f64 * cell_size + 70.0
+        let size = controller.gameboard.size;
+        let cell_size = settings.size / size as f64;
+        //Text is scaled from 4x4 board, three digits are made smaller
+        let font_size = (cell_size * if controller.gameboard.blank() > 100 { 0.28 } else { 0.36 }) as u32;
+        let is_over = controller.animator.is_over();
+        //Tutorial always shows placed tiles
+        let overlay = settings.overlay != controller.overlay || controller.tutorial.is_some();
+        let placed = controller.gameboard.placed();
+        let targets = controller.tutorial.as_ref().map_or(&[][..], |t| t.step().targets);
+        //Big boards have hundreds of cells, so same rectangles and glyphs are drawn together
+        let (mut placed_rects, mut misplaced_rects, mut numbers) = (vec![], vec![], vec![]);
+        for j in 0..size {
+            for i in 0..size {
+                let cell = Position::new(j, i);
+                let ch = controller.gameboard.cell_as_string(cell);
+                //Dragged tile is moved to zero field by its offset
+                let drag_shift = match controller.drag {
+                    Some(drag) if drag.cell == cell => [
+                        (drag.zero.col as f64 - i as f64) * drag.offset * cell_size,
+                        (drag.zero.row as f64 - j as f64) * drag.offset * cell_size,
+                    ],
+                    _ => [0.0; 2]
+                };
+                //Sliding tile goes from its old place
+                let slide_shift = match (animate_shift[0], animate_shift[1]) {
+                    _ if is_over || controller.animate_cell != cell => [0.0; 2],
+                    (x, _) if x < 0.0 => [cell_size + x, 0.0],
+                    (x, _) if x > 0.0 => [x - cell_size, 0.0],
+                    (_, y) if y < 0.0 => [0.0, cell_size + y],
+                    (_, y) if y > 0.0 => [0.0, y - cell_size],
+                    _ => [0.0; 2]
+                };
+                let corner = [
+                    settings.position[0] + i as f64 * cell_size + drag_shift[0] + slide_shift[0],
+                    settings.position[1] + j as f64 * cell_size + drag_shift[1] + slide_shift[1],
                 ];
+                let is_tile = controller.gameboard.is_tile(cell);
+                if overlay && is_tile {
+                    let rects = if placed[cell.index(size)] { &mut placed_rects } else { &mut misplaced_rects };
+                    //Lines between cells stay visible
+                    rects.push([corner[0] + 2.0, corner[1] + 2.0, cell_size - 4.0, cell_size - 4.0]);
+                }
+                if targets.contains(&controller.gameboard.get(cell)) {
+                    Rectangle::new_border(settings.target_color, 2.0)
+                        .draw([corner[0] + 4.0, corner[1] + 4.0, cell_size - 8.0, cell_size - 8.0], &c.draw_state, c.transform, g);
+                }
+                if is_tile {
+                    let width = glyphs.width(font_size, &ch).unwrap_or(0.0);
+                    numbers.push((ch, [corner[0] + (cell_size - width) / 2.0, corner[1] + cell_size * 0.64]));
+                }
+            }
+        }
+        fill_rects(settings.placed_color, &placed_rects, c, g);
+        fill_rects(settings.misplaced_color, &misplaced_rects, c, g);
+        self.draw_numbers(&numbers, font_size, glyphs, c, g);
+    }
 
-                let is_over = controller.animator.is_over();
-                let tupled = (animate_shift[0], animate_shift[1]);
-                let transform = match tupled {
-                    (x, _)  if x < 0.0 => { c.transform.trans(position_in_cell[0] + cell_size + tupled.0, position_in_cell[1]) }
-                    (x, _)  if x > 0.0 => { c.transform.trans(position_in_cell[0] - cell_size + tupled.0, position_in_cell[1]) }
-                    (_, y)  if y < 0.0 => { c.transform.trans(position_in_cell[0], position_in_cell[1] + tupled.1 + cell_size) }
-                    (_, y)  if y > 0.0 => { c.transform.trans(position_in_cell[0], position_in_cell[1] + tupled.1 - cell_size) }
-                    _ => { c.transform.trans(position_in_cell[0], position_in_cell[1]) }
+    ///Numbers from their points on the baseline, every glyph is drawn for all of them at once
+    fn draw_numbers<G: Graphics, C: CharacterCache<Texture=G::Texture>>(&self, numbers: &[(String, [f64; 2])], font_size: u32, glyphs: &mut C, c: &Context, g: &mut G) {
+        //Places and parts of glyph texture of every character
+        let mut quads: BTreeMap<char, Vec<(types::Rectangle, types::SourceRectangle)>> = BTreeMap::new();
+        for (number, [x, y]) in numbers {
+            let mut x = *x;
+            for ch in number.chars() {
+                let character = match glyphs.character(font_size, ch) {
+                    Ok(character) => character,
+                    Err(_) => continue
                 };
-                if controller.animate_cell == [i, j] {
-                    text::Text::new_color(settings.text_color, 40)
-                        .draw(&ch,
-                              glyphs,
-                              &c.draw_state,
-                              if is_over {
-                                  c.transform.trans(position_in_cell[0], position_in_cell[1])
-                              } else { transform },
-                              g).unwrap_or_else(|_| ());
-                } else {
-                    text::Text::new_color(settings.text_color, 40)
-                        .draw(&ch,
-                              glyphs,
-                              &c.draw_state,
-                              c.transform.trans(position_in_cell[0], position_in_cell[1]),
-                              g).unwrap_or_else(|_| ());
-                }
+                let size = character.atlas_size;
+                let quad = ([x + character.left(), y - character.top(), size[0], size[1]],
+                    [character.atlas_offset[0], character.atlas_offset[1], size[0], size[1]]);
+                x += character.advance_width();
+                quads.entry(ch).or_default().push(quad);
+            }
+        }
+        for (ch, list) in &quads {
+            if let Ok(character) = glyphs.character(font_size, *ch) {
+                graphics::image::draw_many(list, self.settings.text_color, character.texture, &c.draw_state, c.transform, g);
             }
         }
     }
+
+    ///Title of the lesson and what to do in its step, or why the move wasn't allowed
+    fn draw_lesson<G: Graphics, C: CharacterCache<Texture=G::Texture>>(&self, controller: &GameboardController, glyphs: &mut C, c: &Context, g: &mut G) {
+        let tutorial = match &controller.tutorial {
+            Some(tutorial) => tutorial,
+            None => return
+        };
+        let settings = &self.settings;
+        let lesson = tutorial.lesson();
+        let title = format!("Lesson {}/{}: {}, step {}/{}, F1 - leave",
+            tutorial.lesson + 1, tutorial::LESSONS.len(), lesson.title, tutorial.step + 1, lesson.steps.len());
+        self.draw_text(&title, 14, [settings.position[0], settings.position[1] - 30.0], glyphs, c, g);
+        let line = match (&tutorial.feedback, controller.explanation) {
+            (Some(feedback), _) => feedback.clone(),
+            (None, Some(goal)) => format!("Hint: {}", goal),
+            (None, None) => tutorial.step().text.to_owned(),
+        };
+        self.draw_text(&line, 16, [settings.position[0], settings.position[1] - 8.0], glyphs, c, g);
+    }
+
+    ///Bar of placed tiles and Manhattan distance left to the goal
+    fn draw_progress_bar<G: Graphics, C: CharacterCache<Texture=G::Texture>>(&self, controller: &GameboardController, glyphs: &mut C, c: &Context, g: &mut G) {
+        let settings = &self.settings;
+        let board = &controller.gameboard;
+        let total = board.tiles();
+        let placed = board.placed().iter().filter(|p| **p).count();
+        let bar = [settings.position[0], settings.position[1] - 42.0, settings.size * 0.3, 10.0];
+        Rectangle::new(settings.zero_color).draw(bar, &c.draw_state, c.transform, g);
+        //Board of zero fields and obstacles only is always in place
+        let part = if total == 0 { 1.0 } else { placed as f64 / total as f64 };
+        Rectangle::new(settings.chart_color).draw([bar[0], bar[1], bar[2] * part, bar[3]], &c.draw_state, c.transform, g);
+        Rectangle::new_border(settings.border_color, 0.5).draw(bar, &c.draw_state, c.transform, g);
+        let text = format!("{}/{} placed, distance {}", placed, total, board.distance());
+        self.draw_text(&text, 12, [bar[0], settings.position[1] - 16.0], glyphs, c, g);
+    }
+
+    ///Next step of animation in pixels of `cell_size`
+    fn animate_shift(&self, controller: &mut GameboardController, cell_size: f64) -> [f64; 2] {
+        let shift = controller.animator.animate(controller.animate_direction);
+        let scale = cell_size / controller.animator.distance();
+        [shift[0] * scale, shift[1] * scale]
+    }
+
     fn draw_board<G: Graphics>(&self, c: &Context, g: &mut G) {
         let settings = &self.settings;
         let board_rect = [
@@ -151,55 +727,86 @@ impl GameboardView {
         Rectangle::new_border(settings.border_color, settings.board_edge_radius)
             .draw(board_rect, &c.draw_state, c.transform, g);
     }
-    fn draw_fields<G: Graphics>(&self, controller: &mut GameboardController, animate_shift:[f64;2], c: &Context, g: &mut G) {
+    fn draw_fields<G: Graphics>(&self, controller: &GameboardController, animate_shift: [f64; 2], c: &Context, g: &mut G) {
         let settings = &self.settings;
-        let animate_shift = controller.animator.animate(controller.animate_direction);
-        //Zero rectangle
-        let zx = controller.gameboard.zero()[0];
-        let zy = controller.gameboard.zero()[1];
-
-        if controller.animator.is_over() {
-            animate_cell_static(settings, animate_shift, (zx, zy), c, g);
+        let board = &controller.gameboard;
+        let cell_size = settings.size / board.size as f64;
+        //Zero rectangle of the moving zero field, the others stay
+        let moving = if let Some(drag) = controller.drag {
+            drag_cell(settings, cell_size, drag, c, g);
+            Some(drag.zero)
+        } else if controller.animator.is_over() {
+            None
         } else {
-            animate_cell_moving(settings, animate_shift, (zx, zy), controller.animate_direction, c, g);
+            let zero = controller.moving_zero();
+            animate_cell_moving(settings, cell_size, animate_shift, (zero.col, zero.row), controller.animate_direction, c, g);
+            Some(zero)
+        };
+        let shift = if moving.is_some() { [0.0; 2] } else { animate_shift };
+        for zero in board.zeros().into_iter().filter(|zero| Some(*zero) != moving) {
+            animate_cell_static(settings, cell_size, shift, (zero.col, zero.row), c, g);
         }
+        let obstacles: Vec<[f64; 4]> = (0..board.cells.len())
+            .map(|i| Position::from_index(i, board.size))
+            .filter(|cell| board.is_obstacle(*cell))
+            .map(|cell| [settings.position[0] + cell_size * cell.col as f64, settings.position[1] + cell_size * cell.row as f64, cell_size, cell_size])
+            .collect();
+        fill_rects(settings.obstacle_color, &obstacles, c, g);
 
-        fn animate_cell_moving<G: Graphics>(settings: &GameboardViewSettings, animate_shift: [f64; 2], zero: (usize, usize), animate_direction: Direction, c: &Context, g: &mut G) {
+        fn animate_cell_moving<G: Graphics>(settings: &GameboardViewSettings, cell_size: f64, animate_shift: [f64; 2], zero: (usize, usize), animate_direction: Direction, c: &Context, g: &mut G) {
             let (x, y, width, height) = match animate_direction {
-                Direction::Top => (0.0, 110.0 + animate_shift[1], 110.0, animate_shift[1]),
-                Direction::Right => (0.0, 0.0, animate_shift[0], 110.0),
-                Direction::Bottom => (0.0, 0.0, 110.0, animate_shift[1]),
-                Direction::Left => (animate_shift[0] + 110.0, 0.0, animate_shift[0], 110.0),
+                Direction::Top => (0.0, cell_size + animate_shift[1], cell_size, animate_shift[1]),
+                Direction::Right => (0.0, 0.0, animate_shift[0], cell_size),
+                Direction::Bottom => (0.0, 0.0, cell_size, animate_shift[1]),
+                Direction::Left => (animate_shift[0] + cell_size, 0.0, animate_shift[0], cell_size),
             };
             let prev_zero_rect = [
-                settings.position[0] + settings.size / FSIZE * zero.0 as f64 + x,
-                settings.position[1] + settings.size / FSIZE * zero.1 as f64 + y,
+                settings.position[0] + cell_size * zero.0 as f64 + x,
+                settings.position[1] + cell_size * zero.1 as f64 + y,
                 f64::abs(width), f64::abs(height)
             ];
-            println!("{:?}", prev_zero_rect);
 
             let (x, y) = match animate_direction {
-                Direction::Top => (0.0, -110.0),
-                Direction::Right => (110.0 + animate_shift[0], 0.0),
-                Direction::Bottom => (0.0, 110.0 + animate_shift[1]),
-                Direction::Left => (-110.0, 0.0),
+                Direction::Top => (0.0, -cell_size),
+                Direction::Right => (cell_size + animate_shift[0], 0.0),
+                Direction::Bottom => (0.0, cell_size + animate_shift[1]),
+                Direction::Left => (-cell_size, 0.0),
             };
             let next_zero_rect = [
-                settings.position[0] + settings.size / FSIZE * zero.0 as f64 + x,
-                settings.position[1] + settings.size / FSIZE * zero.1 as f64 + y,
-                settings.size / FSIZE - f64::abs(animate_shift[0]),
-                settings.size / FSIZE - f64::abs(animate_shift[1])
+                settings.position[0] + cell_size * zero.0 as f64 + x,
+                settings.position[1] + cell_size * zero.1 as f64 + y,
+                cell_size - f64::abs(animate_shift[0]),
+                cell_size - f64::abs(animate_shift[1])
             ];
 
             Rectangle::new(settings.zero_color).draw(prev_zero_rect, &c.draw_state, c.transform, g);
 
             Rectangle::new(settings.zero_color).draw(next_zero_rect, &c.draw_state, c.transform, g);
         }
-        fn animate_cell_static<G: Graphics>(settings: &GameboardViewSettings, animate_shift: [f64; 2], zero: (usize, usize), c: &Context, g: &mut G) {
+        fn drag_cell<G: Graphics>(settings: &GameboardViewSettings, cell_size: f64, drag: Drag, c: &Context, g: &mut G) {
+            let zero = drag.zero;
+            //Tile and zero field make rectangle of two cells, the tile covers a part of it
+            let (dx, dy) = (zero.col as f64 - drag.cell.col as f64, zero.row as f64 - drag.cell.row as f64);
+            let left = settings.position[0] + cell_size * drag.cell.col.min(zero.col) as f64;
+            let top = settings.position[1] + cell_size * drag.cell.row.min(zero.row) as f64;
+            let right = left + cell_size * (1.0 + dx.abs());
+            let bottom = top + cell_size * (1.0 + dy.abs());
+            let tile_left = settings.position[0] + cell_size * (drag.cell.col as f64 + dx * drag.offset);
+            let tile_top = settings.position[1] + cell_size * (drag.cell.row as f64 + dy * drag.offset);
+            let parts = if dx != 0.0 {
+                [[left, top, tile_left - left, cell_size], [tile_left + cell_size, top, right - tile_left - cell_size, cell_size]]
+            } else {
+                [[left, top, cell_size, tile_top - top], [left, tile_top + cell_size, cell_size, bottom - tile_top - cell_size]]
+            };
+            for part in parts.iter().filter(|p| p[2] > 0.0 && p[3] > 0.0) {
+                Rectangle::new(settings.zero_color).draw(*part, &c.draw_state, c.transform, g);
+            }
+        }
+        fn animate_cell_static<G: Graphics>(settings: &GameboardViewSettings, cell_size: f64, animate_shift: [f64; 2], zero: (usize, usize), c: &Context, g: &mut G) {
             let zero_rect = [
-                settings.position[0] + settings.size / FSIZE * zero.0 as f64,
-                settings.position[1] + settings.size / FSIZE * zero.1 as f64,
-                settings.size / FSIZE - animate_shift[0], settings.size / FSIZE - animate_shift[1],
+                settings.position[0] + cell_size * zero.0 as f64,
+                settings.position[1] + cell_size * zero.1 as f64,
+                cell_size - animate_shift[0], cell_size - animate_shift[1],
             ];
             Rectangle::new(settings.zero_color)
                 .draw(zero_rect, &c.draw_state, c.transform, g);
@@ -208,44 +815,210 @@ impl GameboardView {
         //Selected rectangle
         if let Some(sel) = controller.selected {
             let selected_rect = [
-                settings.position[0] + settings.size / FSIZE * sel[0] as f64,
-                settings.position[1] + settings.size / FSIZE * sel[1] as f64,
-                settings.size / FSIZE, settings.size / FSIZE,
+                settings.position[0] + cell_size * sel.col as f64,
+                settings.position[1] + cell_size * sel.row as f64,
+                cell_size, cell_size,
             ];
 
             Rectangle::new(settings.selected_color)
                 .draw(selected_rect, &c.draw_state, c.transform, g);
         }
     }
-    fn draw_lines<G: Graphics>(&self, c: &Context, g: &mut G) {
+    fn draw_lines<G: Graphics>(&self, size: usize, c: &Context, g: &mut G) {
         let settings = &self.settings;
-        let cell_edge = Line::new(settings.between_color, settings.cell_edge_radius);
-
-        for i in 0..=4 {
-            let x = settings.position[0] + i as f64 / FSIZE * settings.size;
-            let y = settings.position[1] + i as f64 / FSIZE * settings.size;
-            let x2 = settings.position[0] + settings.size;
-            let y2 = settings.position[1] + settings.size;
-
-            let vline = [x, settings.position[1], x, y2];
-            cell_edge.draw(vline, &c.draw_state, c.transform, g);
-
-            let hline = [settings.position[0], y, x2, y];
-            cell_edge.draw(hline, &c.draw_state, c.transform, g);
-        }
+        let radius = settings.cell_edge_radius;
+        //Lines are thin rectangles of one draw call
+        let lines: Vec<[f64; 4]> = (0..=size).flat_map(|i| {
+            let shift = i as f64 / size as f64 * settings.size;
+            let (x, y) = (settings.position[0] + shift, settings.position[1] + shift);
+            [
+                [x - radius, settings.position[1] - radius, 2.0 * radius, settings.size + 2.0 * radius],
+                [settings.position[0] - radius, y - radius, settings.size + 2.0 * radius, 2.0 * radius],
+            ]
+        }).collect();
+        fill_rects(settings.between_color, &lines, c, g);
     }
     fn draw_points<G: Graphics, C: CharacterCache<Texture=G::Texture>>(&self, controller: &GameboardController, glyphs: &mut C, c: &Context, g: &mut G) {
         let settings = &self.settings;
-        let points = format!("Moves: {}", controller.gameboard.moves.to_string());
+        let points = format!("Moves: {}", controller.gameboard.moves);
         text::Text::new_color(settings.text_color, 40)
             .draw(&points,
                   glyphs,
                   &c.draw_state,
-                  c.transform.trans(settings.size / FSIZE * 1.5, settings.position[1] - 10.0),
-                  g).unwrap_or_else(|_| ());
+                  c.transform.trans(settings.size * 0.375, settings.position[1] - 10.0),
+                  g).unwrap_or(());
     }
 }
 
 
+///Rectangles of one color in one draw call
+fn fill_rects<G: Graphics>(color: Color, rects: &[[f64; 4]], c: &Context, g: &mut G) {
+    if rects.is_empty() {
+        return;
+    }
+    g.tri_list(&c.draw_state, &color, |f| for rect in rects {
+        f(&triangulation::rect_tri_list_xy(c.transform, *rect));
+    });
+}
+
 
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::animator::PlainAnimator;
+    use crate::software;
+
+    ///Compare picture of `controller` screen with `assets/golden/<name>.png`,
+    ///`UPDATE_GOLDEN=1 cargo test` writes new pictures
+    fn check_golden(name: &str, controller: &mut GameboardController) {
+        let mut glyphs = software::glyphs("assets/amazone.ttf").unwrap();
+        let view = GameboardView::new(GameboardViewSettings::new());
+        let path = Path::new("assets/golden").join(format!("{}.png", name));
+        if std::env::var_os("UPDATE_GOLDEN").is_some() {
+            view.export_png(controller, &mut glyphs, &path).unwrap();
+            return;
+        }
+        let image = view.render(controller, &mut glyphs);
+        let golden = image::open(&path).unwrap().to_rgba();
+        assert_eq!(image.dimensions(), golden.dimensions());
+        let different = image.pixels().zip(golden.pixels())
+            .filter(|(a, b)| a.0.iter().zip(b.0.iter()).any(|(x, y)| (*x as i32 - *y as i32).abs() > 2))
+            .count();
+        assert_eq!(different, 0, "{} pixels differ from {}", different, path.display());
+    }
+
+    #[test]
+    fn golden_test() {
+        let board = "1 2 3 4/5 6 7 8/9 10 11 12/13 14 0 15".parse().unwrap();
+        let mut controller = GameboardController::new(board, Box::new(PlainAnimator::new(100.0, 10.0)));
+        check_golden("prepare", &mut controller);
+        controller.game_state = GameState::InProcess;
+        controller.selected = Some(Position::new(1, 1));
+        check_golden("progress", &mut controller);
+        controller.overlay = true;
+        check_golden("overlay", &mut controller);
+        controller.start_tutorial();
+        check_golden("tutorial", &mut controller);
+    }
+
+    #[test]
+    fn torus_test() {
+        use crate::gameboard::Gameboard;
+        use crate::bindings::Action;
+        use crate::topology::Topology;
+        let board = Gameboard { topology: Topology::Torus, ..Gameboard::solved(3, Default::default()) };
+        let mut controller = GameboardController::new(board, Box::new(PlainAnimator::new(100.0, 10.0)));
+        controller.game_state = GameState::InProcess;
+        //Tile 7 goes out at the left edge and comes in at the right one
+        controller.act(Action::Move(Direction::Left));
+        assert_eq!(controller.gameboard.zero(), Position::new(2, 0));
+        for _ in 0..4 {
+            controller.animator.animate(Direction::Left);
+        }
+        check_golden("torus", &mut controller);
+    }
+
+    #[test]
+    fn designer_test() {
+        use crate::bindings::Action;
+        let board = "1 2 3 4/5 # 6 7/8 9 # 10/11 12 0 0".parse().unwrap();
+        let mut controller = GameboardController::new(board, Box::new(PlainAnimator::new(100.0, 10.0)));
+        controller.game_state = GameState::InProcess;
+        controller.overlay = true;
+        //Tile 12 slides right, the other zero field and obstacles stay
+        controller.act(Action::Move(Direction::Right));
+        for _ in 0..4 {
+            controller.animator.animate(Direction::Right);
+        }
+        check_golden("designer", &mut controller);
+    }
+
+    #[test]
+    fn theme_test() {
+        for theme in Theme::ALL.iter() {
+            assert_eq!(theme.to_string().parse::<Theme>(), Ok(*theme));
+            //Only learning theme shows the overlay before the key is pressed
+            assert_eq!(GameboardViewSettings::with_theme(*theme).overlay, *theme == Theme::Learning);
+        }
+        assert_eq!(Theme::Learning.next(), Theme::Light);
+        let board = "1 2 3/4 5 6/7 0 8".parse().unwrap();
+        let mut controller = GameboardController::new(board, Box::new(PlainAnimator::new(100.0, 10.0)));
+        controller.game_state = GameState::InProcess;
+        let mut glyphs = software::glyphs("assets/amazone.ttf").unwrap();
+        let render = |theme, controller: &mut GameboardController, glyphs: &mut _| {
+            GameboardView::new(GameboardViewSettings::with_theme(theme)).render(controller, glyphs)
+        };
+        let learning = render(Theme::Learning, &mut controller, &mut glyphs);
+        assert!(learning.pixels().ne(render(Theme::Light, &mut controller, &mut glyphs).pixels()));
+        //Overlay key hides it again
+        controller.overlay = true;
+        let hidden = render(Theme::Learning, &mut controller, &mut glyphs);
+        controller.overlay = false;
+        assert!(hidden.pixels().eq(render(Theme::Light, &mut controller, &mut glyphs).pixels()));
+    }
+
+    #[test]
+    fn start_hint_test() {
+        use crate::bindings::Bindings;
+        let board = "1 2 3/4 5 6/7 0 8".parse().unwrap();
+        let mut controller = GameboardController::new(board, Box::new(PlainAnimator::new(100.0, 10.0)));
+        let mut glyphs = software::glyphs("assets/amazone.ttf").unwrap();
+        let view = GameboardView::new(GameboardViewSettings::new());
+        let space = view.render(&mut controller, &mut glyphs);
+        //Prepare screen names the bound key
+        controller.bindings = Bindings::from_json(r#"{"start": ["Gamepad:0", "Return"]}"#).unwrap();
+        assert!(space.pixels().ne(view.render(&mut controller, &mut glyphs).pixels()));
+        controller.bindings = Bindings::from_json(r#"{"start": ["Gamepad:0", "Space"]}"#).unwrap();
+        assert!(space.pixels().eq(view.render(&mut controller, &mut glyphs).pixels()));
+    }
+
+    #[test]
+    fn empty_progress_test() {
+        let board = "0 #/# 0".parse().unwrap();
+        let mut controller = GameboardController::new(board, Box::new(PlainAnimator::new(100.0, 10.0)));
+        controller.game_state = GameState::InProcess;
+        controller.overlay = true;
+        let mut glyphs = software::glyphs("assets/amazone.ttf").unwrap();
+        let view = GameboardView::new(GameboardViewSettings::new());
+        let image = view.render(&mut controller, &mut glyphs);
+        //Bar without tiles is full
+        let [x, y] = [view.settings.position[0] + view.settings.size * 0.25, view.settings.position[1] - 37.0];
+        let pixel = image.get_pixel(x as u32, y as u32);
+        let chart = view.settings.chart_color.map(|v| (v * 255.0).round() as i32);
+        assert!(pixel.0.iter().zip(chart.iter()).all(|(a, b)| (*a as i32 - b).abs() <= 2), "{:?}", pixel);
+    }
+
+    #[test]
+    fn zoom_test() {
+        use piston::input::{ButtonArgs, ButtonState, Event, Input, Motion};
+        use crate::gameboard::Gameboard;
+        let press = |button| Event::Input(Input::Button(ButtonArgs { state: ButtonState::Press, button, scancode: None }), None);
+        let mut controller = GameboardController::new(Gameboard::solved(16, Default::default()), Box::new(PlainAnimator::new(100.0, 10.0)));
+        controller.game_state = GameState::InProcess;
+        check_golden("large", &mut controller);
+
+        let mut view = GameboardView::new(GameboardViewSettings::new());
+        assert!(view.event(&controller, &press(Button::Keyboard(Key::Equals))));
+        assert_eq!(view.viewport.zoom, 1.25);
+        //Zero field in the corner is kept shown
+        assert_eq!(view.viewport.offset, [110.0, 110.0]);
+        assert_eq!(view.board_area(), ([-100.0, -60.0], 550.0));
+        for _ in 0..10 {
+            view.event(&controller, &press(Button::Keyboard(Key::Equals)));
+        }
+        assert_eq!(view.viewport.zoom, 4.0);
+        view.pan_by([-10000.0, 0.0]);
+        assert_eq!(view.viewport.offset[0], 0.0);
+        //Hidden cells above the shown part can't be clicked
+        view.event(&controller, &Event::Input(Input::Move(Motion::MouseCursor([100.0, 20.0])), None));
+        assert!(!view.event(&controller, &press(Button::Mouse(MouseButton::Left))));
+        assert!(view.event(&controller, &press(Button::Keyboard(Key::Minus))));
+
+        let mut glyphs = software::glyphs("assets/amazone.ttf").unwrap();
+        let image = view.render(&mut controller, &mut glyphs);
+        assert_eq!(image.get_pixel(5, 30).0, [255; 4]);
+        assert_ne!(image.get_pixel(100, 100).0, [255; 4]);
+    }
+}
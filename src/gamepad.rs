@@ -0,0 +1,290 @@
+//! Gamepads for the glutin window, which sends no controller events.
+//! Devices are read here and turned into piston controller events numbered as SDL game controllers,
+//! so bindings are the same as with `sdl2_window`. Only Linux evdev devices are read now.
+
+use piston::input::{Button, ButtonArgs, ButtonState, ControllerAxisArgs, ControllerButton, ControllerHat,
+    Event, HatState, Input, Motion};
+
+///Seconds between searches of plugged gamepads
+pub const RESCAN_SECONDS: u64 = 2;
+
+//Коды evdev из linux/input-event-codes.h
+const BTN_SOUTH: u16 = 0x130;
+const BTN_EAST: u16 = 0x131;
+const BTN_NORTH: u16 = 0x133;
+const BTN_WEST: u16 = 0x134;
+const BTN_TL: u16 = 0x136;
+const BTN_TR: u16 = 0x137;
+const BTN_SELECT: u16 = 0x13a;
+const BTN_START: u16 = 0x13b;
+const BTN_MODE: u16 = 0x13c;
+const BTN_THUMBL: u16 = 0x13d;
+const BTN_THUMBR: u16 = 0x13e;
+const BTN_DPAD_UP: u16 = 0x220;
+const BTN_DPAD_DOWN: u16 = 0x221;
+const BTN_DPAD_LEFT: u16 = 0x222;
+const BTN_DPAD_RIGHT: u16 = 0x223;
+const ABS_X: u16 = 0x00;
+const ABS_Y: u16 = 0x01;
+const ABS_RX: u16 = 0x03;
+const ABS_RY: u16 = 0x04;
+const ABS_HAT0X: u16 = 0x10;
+const ABS_HAT0Y: u16 = 0x11;
+
+///SDL game controller number of evdev button `code`
+pub fn button_number(code: u16) -> Option<u8> {
+    match code {
+        BTN_SOUTH => Some(0),
+        BTN_EAST => Some(1),
+        BTN_WEST => Some(2),
+        BTN_NORTH => Some(3),
+        BTN_SELECT => Some(4),
+        BTN_MODE => Some(5),
+        BTN_START => Some(6),
+        BTN_THUMBL => Some(7),
+        BTN_THUMBR => Some(8),
+        BTN_TL => Some(9),
+        BTN_TR => Some(10),
+        BTN_DPAD_UP => Some(11),
+        BTN_DPAD_DOWN => Some(12),
+        BTN_DPAD_LEFT => Some(13),
+        BTN_DPAD_RIGHT => Some(14),
+        _ => None
+    }
+}
+
+///SDL game controller number of evdev stick axis `code`
+pub fn axis_number(code: u16) -> Option<u8> {
+    match code {
+        ABS_X => Some(0),
+        ABS_Y => Some(1),
+        ABS_RX => Some(2),
+        ABS_RY => Some(3),
+        _ => None
+    }
+}
+
+///Hat state of evdev hat axes, negative `y` is up
+pub fn hat_state(x: i32, y: i32) -> HatState {
+    match (x.signum(), y.signum()) {
+        (0, -1) => HatState::Up,
+        (0, 1) => HatState::Down,
+        (-1, 0) => HatState::Left,
+        (1, 0) => HatState::Right,
+        (-1, -1) => HatState::LeftUp,
+        (1, -1) => HatState::RightUp,
+        (-1, 1) => HatState::LeftDown,
+        (1, 1) => HatState::RightDown,
+        _ => HatState::Centered
+    }
+}
+
+///Axis `value` of `min..=max` range as -1..1 position
+pub fn axis_position(value: i32, min: i32, max: i32) -> f64 {
+    if max <= min {
+        return 0.0;
+    }
+    let position = (value as f64 - min as f64) / (max as f64 - min as f64) * 2.0 - 1.0;
+    position.clamp(-1.0, 1.0)
+}
+
+fn button_event(button: Button, pressed: bool) -> Event {
+    let state = if pressed { ButtonState::Press } else { ButtonState::Release };
+    Event::Input(Input::Button(ButtonArgs { state, button, scancode: None }), None)
+}
+
+///Piston event of evdev event with `code` and `value`, `hat` keeps position of hat axes, `range` gives range of an axis
+fn event_of(id: i32, kind: Kind, code: u16, value: i32, hat: &mut [i32; 2], range: impl Fn(u16) -> (i32, i32)) -> Option<Event> {
+    match kind {
+        //2 - автоповтор, он не нужен
+        Kind::Key if value != 2 => button_number(code)
+            .map(|n| button_event(Button::Controller(ControllerButton::new(id, n)), value == 1)),
+        Kind::Abs if code == ABS_HAT0X || code == ABS_HAT0Y => {
+            hat[(code - ABS_HAT0X) as usize] = value;
+            Some(button_event(Button::Hat(ControllerHat::new(id, 0, hat_state(hat[0], hat[1]))), true))
+        },
+        Kind::Abs => axis_number(code).map(|axis| {
+            let (min, max) = range(code);
+            Event::Input(Input::Move(Motion::ControllerAxis(ControllerAxisArgs::new(id, axis, axis_position(value, min, max)))), None)
+        }),
+        _ => None
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Kind {
+    Key,
+    Abs,
+    Other
+}
+
+///Plugged gamepads
+pub struct Gamepads {
+    #[cfg(target_os = "linux")]
+    pads: Vec<linux::Pad>,
+    #[cfg(target_os = "linux")]
+    next_id: i32,
+    scanned: Option<std::time::Instant>
+}
+
+impl Gamepads {
+    ///No gamepads, they are searched on the first poll
+    pub fn new() -> Gamepads {
+        Gamepads {
+            #[cfg(target_os = "linux")]
+            pads: Vec::new(),
+            #[cfg(target_os = "linux")]
+            next_id: 0,
+            scanned: None
+        }
+    }
+
+    ///Events of all gamepads since the last poll. Unplugged gamepads are dropped, new ones are searched every few seconds
+    pub fn poll(&mut self) -> Vec<Event> {
+        let now = std::time::Instant::now();
+        if self.scanned.is_none_or(|t| now.duration_since(t).as_secs() >= RESCAN_SECONDS) {
+            self.scanned = Some(now);
+            self.scan();
+        }
+        self.read()
+    }
+
+    #[cfg(target_os = "linux")]
+    fn scan(&mut self) {
+        for (path, device) in evdev::enumerate() {
+            if self.pads.iter().any(|p| p.path == path) {
+                continue;
+            }
+            if let Some(pad) = linux::Pad::open(self.next_id, path, device) {
+                self.next_id += 1;
+                self.pads.push(pad);
+            }
+        }
+    }
+
+    #[cfg(target_os = "linux")]
+    fn read(&mut self) -> Vec<Event> {
+        let mut events = Vec::new();
+        self.pads.retain_mut(|pad| pad.read(&mut events));
+        events
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    fn scan(&mut self) {}
+
+    #[cfg(not(target_os = "linux"))]
+    fn read(&mut self) -> Vec<Event> {
+        Vec::new()
+    }
+}
+
+impl Default for Gamepads {
+    fn default() -> Gamepads {
+        Gamepads::new()
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod linux {
+    use std::io;
+    use std::os::unix::io::AsRawFd;
+    use std::path::PathBuf;
+    use evdev::{Device, InputEventKind, Key};
+    use piston::input::Event;
+    use super::{event_of, Kind};
+
+    pub struct Pad {
+        id: i32,
+        pub path: PathBuf,
+        device: Device,
+        ///min and max of absolute axes by their codes
+        ranges: Vec<(i32, i32)>,
+        hat: [i32; 2]
+    }
+
+    impl Pad {
+        ///Gamepad of `device`, None for other devices
+        pub fn open(id: i32, path: PathBuf, device: Device) -> Option<Pad> {
+            if !device.supported_keys().is_some_and(|keys| keys.contains(Key::BTN_SOUTH)) {
+                return None;
+            }
+            let ranges = device.get_abs_state().ok()?.iter().map(|a| (a.minimum, a.maximum)).collect();
+            //Чтение не должно останавливать окно
+            let fd = device.as_raw_fd();
+            let nonblocking = unsafe {
+                let flags = libc::fcntl(fd, libc::F_GETFL);
+                flags >= 0 && libc::fcntl(fd, libc::F_SETFL, flags | libc::O_NONBLOCK) >= 0
+            };
+            if !nonblocking {
+                return None;
+            }
+            Some(Pad { id, path, device, ranges, hat: [0; 2] })
+        }
+
+        ///Add events since the last read, false if the gamepad is gone
+        pub fn read(&mut self, events: &mut Vec<Event>) -> bool {
+            let fetched = match self.device.fetch_events() {
+                Ok(fetched) => fetched,
+                Err(e) => return e.kind() == io::ErrorKind::WouldBlock
+            };
+            let ranges = &self.ranges;
+            for e in fetched {
+                let kind = match e.kind() {
+                    InputEventKind::Key(_) => Kind::Key,
+                    InputEventKind::AbsAxis(_) => Kind::Abs,
+                    _ => Kind::Other
+                };
+                let range = |code: u16| ranges.get(code as usize).copied().unwrap_or((-1, 1));
+                events.extend(event_of(self.id, kind, e.code(), e.value(), &mut self.hat, range));
+            }
+            true
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use piston::input::{ControllerAxisEvent, PressEvent, ReleaseEvent};
+
+    #[test]
+    fn mapping_test() {
+        assert_eq!(button_number(BTN_SOUTH), Some(0));
+        assert_eq!(button_number(BTN_EAST), Some(1));
+        assert_eq!(button_number(BTN_WEST), Some(2));
+        assert_eq!(button_number(BTN_START), Some(6));
+        assert_eq!(button_number(BTN_TR), Some(10));
+        assert_eq!(button_number(BTN_DPAD_DOWN), Some(12));
+        assert_eq!(button_number(0x100), None);
+        assert_eq!(axis_number(ABS_Y), Some(1));
+        assert_eq!(axis_number(ABS_HAT0X), None);
+        assert_eq!(hat_state(0, -1), HatState::Up);
+        assert_eq!(hat_state(-1, 0), HatState::Left);
+        assert_eq!(hat_state(1, 1), HatState::RightDown);
+        assert_eq!(hat_state(0, 0), HatState::Centered);
+        assert_eq!(axis_position(0, -255, 255), 0.0);
+        assert_eq!(axis_position(-255, -255, 255), -1.0);
+        assert_eq!(axis_position(0, 0, 255), -1.0);
+        assert_eq!(axis_position(255, 0, 255), 1.0);
+        assert_eq!(axis_position(300, 0, 255), 1.0);
+        assert_eq!(axis_position(5, 0, 0), 0.0);
+    }
+
+    #[test]
+    fn event_test() {
+        let mut hat = [0; 2];
+        let range = |_| (0, 255);
+        let press = event_of(3, Kind::Key, BTN_EAST, 1, &mut hat, range).unwrap();
+        assert_eq!(press.press_args(), Some(Button::Controller(ControllerButton::new(3, 1))));
+        let release = event_of(3, Kind::Key, BTN_EAST, 0, &mut hat, range).unwrap();
+        assert_eq!(release.release_args(), Some(Button::Controller(ControllerButton::new(3, 1))));
+        assert!(event_of(3, Kind::Key, BTN_EAST, 2, &mut hat, range).is_none());
+        let left = event_of(3, Kind::Abs, ABS_HAT0X, -1, &mut hat, range).unwrap();
+        assert_eq!(left.press_args(), Some(Button::Hat(ControllerHat::new(3, 0, HatState::Left))));
+        let left_up = event_of(3, Kind::Abs, ABS_HAT0Y, -1, &mut hat, range).unwrap();
+        assert_eq!(left_up.press_args(), Some(Button::Hat(ControllerHat::new(3, 0, HatState::LeftUp))));
+        let stick = event_of(3, Kind::Abs, ABS_X, 255, &mut hat, range).unwrap();
+        assert_eq!(stick.controller_axis_args(), Some(ControllerAxisArgs::new(3, 0, 1.0)));
+        assert!(event_of(3, Kind::Other, BTN_EAST, 1, &mut hat, range).is_none());
+    }
+}
@@ -0,0 +1,79 @@
+//! Boards with known optimal solution length, for daily challenges and puzzle packs.
+//! Board is walked away from the goal by random moves. When it is still closer than
+//! the target, it gets as many moves as are missing, so it never goes past the target.
+
+use std::ops::RangeInclusive;
+
+use rand::Rng;
+
+use crate::animator::Direction;
+use crate::gameboard::Gameboard;
+use crate::goal::GoalLayout;
+use crate::solver::{self, Solution};
+
+///Walks from the goal before the target is given up as too long for the board
+const ATTEMPTS: usize = 50;
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Generated {
+    pub board: Gameboard,
+    ///Optimal solution checked by the solver
+    pub solution: Solution,
+}
+
+///Board with optimal solution length in `lengths`.
+///None if it isn't found, when lengths are longer than any solution of the board
+pub fn generate<R: Rng>(rng: &mut R, size: usize, goal: GoalLayout, lengths: RangeInclusive<usize>, max_nodes: Option<u64>) -> Option<Generated> {
+    let target = rng.gen_range(*lengths.start(), *lengths.end() + 1);
+    for _ in 0..ATTEMPTS {
+        let mut board = Gameboard::solved(size, goal);
+        let mut previous: Option<Direction> = None;
+        let mut length = 0;
+        //Every round makes the board at least one move farther, or the walk is started again
+        for _ in 0..target.max(1) * 4 {
+            for _ in length..target {
+                let directions: Vec<Direction> = Direction::ALL.iter().copied()
+                    .filter(|d| previous != Some(d.opposite()) && board.neighbour(board.zero(), *d).is_some())
+                    .collect();
+                let direction = directions[rng.gen_range(0, directions.len())];
+                board.move_zero(direction);
+                previous = Some(direction);
+            }
+            let solution = match solver::solve_limited(&board, max_nodes) {
+                Ok(solution) => solution,
+                Err(_) => break
+            };
+            length = solution.moves.len();
+            if length == target {
+                board.moves = 0;
+                return Some(Generated { board, solution });
+            }
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use crate::gameboard::SeededRng;
+
+    #[test]
+    fn generate_test() {
+        let mut rng = SeededRng::seed_from_u64(1);
+        for _ in 0..10 {
+            let generated = generate(&mut rng, 3, GoalLayout::Standard, 10..=14, None).unwrap();
+            let length = generated.solution.moves.len();
+            assert!((10..=14).contains(&length));
+            assert_eq!(solver::solve(&generated.board).unwrap().moves.len(), length);
+        }
+        let first = generate(&mut SeededRng::seed_from_u64(2), 4, GoalLayout::Snake, 20..=20, None).unwrap();
+        let second = generate(&mut SeededRng::seed_from_u64(2), 4, GoalLayout::Snake, 20..=20, None).unwrap();
+        assert_eq!(first, second);
+        assert_eq!(first.solution.moves.len(), 20);
+        assert_eq!(first.board.goal, GoalLayout::Snake);
+        //Longest optimal solution on 2x2 board is 6 moves
+        assert_eq!(generate(&mut rng, 2, GoalLayout::Standard, 7..=7, None), None);
+    }
+}
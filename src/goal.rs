@@ -0,0 +1,112 @@
+//! Goal layouts: the order of tiles which wins the game.
+
+use crate::gameboard::Position;
+
+#[derive(Debug, Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Hash, Default)]
+pub enum GoalLayout {
+    ///1..15 row by row, zero field last
+    #[default]
+    Standard,
+    ///Zero field in the top-left corner, then 1..15 row by row
+    BlankFirst,
+    ///Boustrophedon: odd rows go right to left, zero field at the end of the path
+    Snake,
+    ///Clockwise spiral from the top-left corner, zero field in the middle
+    Spiral,
+}
+
+impl GoalLayout {
+    pub const ALL: [GoalLayout; 4] = [GoalLayout::Standard, GoalLayout::BlankFirst, GoalLayout::Snake, GoalLayout::Spiral];
+
+    ///Layout after this one, used for switching in menus
+    pub fn next(self) -> GoalLayout {
+        let i = GoalLayout::ALL.iter().position(|g| *g == self).unwrap_or(0);
+        GoalLayout::ALL[(i + 1) % GoalLayout::ALL.len()]
+    }
+
+    ///Positions of board with width `size` in the order tiles are counted
+    pub fn path(self, size: usize) -> Vec<Position> {
+        match self {
+            GoalLayout::Standard | GoalLayout::BlankFirst => (0..size * size).map(|i| Position::from_index(i, size)).collect(),
+            GoalLayout::Snake => (0..size)
+                .flat_map(|row| (0..size).map(move |i| Position::new(row, if row % 2 == 0 { i } else { size - 1 - i })))
+                .collect(),
+            GoalLayout::Spiral => {
+                let mut path = vec![];
+                let (mut top, mut left, mut bottom, mut right) = (0, 0, size - 1, size - 1);
+                while path.len() < size * size {
+                    path.extend((left..=right).map(|col| Position::new(top, col)));
+                    path.extend((top + 1..=bottom).map(|row| Position::new(row, right)));
+                    if top < bottom {
+                        path.extend((left..right).rev().map(|col| Position::new(bottom, col)));
+                    }
+                    if left < right {
+                        path.extend((top + 1..bottom).rev().map(|row| Position::new(row, left)));
+                    }
+                    top += 1;
+                    left += 1;
+                    bottom = bottom.saturating_sub(1);
+                    right = right.saturating_sub(1);
+                }
+                path
+            }
+        }
+    }
+
+    ///Solved cells, zero field has value of `size * size`
+    pub fn cells(self, size: usize) -> Vec<u16> {
+        let mut cells = vec![0; size * size];
+        let path = self.path(size);
+        match self {
+            GoalLayout::BlankFirst => {
+                cells[path[0].index(size)] = (size * size) as u16;
+                for (i, position) in path.iter().enumerate().skip(1) {
+                    cells[position.index(size)] = i as u16;
+                }
+            }
+            _ => {
+                for (i, position) in path.iter().enumerate() {
+                    cells[position.index(size)] = i as u16 + 1;
+                }
+            }
+        }
+        cells
+    }
+
+    ///Place of `value` on solved board with width `size`
+    pub fn position_of(self, value: u16, size: usize) -> Position {
+        let index = self.cells(size).iter().position(|v| *v == value).unwrap_or(0);
+        Position::from_index(index, size)
+    }
+}
+
+names!(GoalLayout, "goal layout", {
+    Standard => "standard",
+    BlankFirst => "blank-first",
+    Snake => "snake",
+    Spiral => "spiral",
+});
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cells_test() {
+        assert_eq!(GoalLayout::Standard.cells(4), [1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16]);
+        assert_eq!(GoalLayout::BlankFirst.cells(4), [16, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15]);
+        assert_eq!(GoalLayout::Snake.cells(4), [1, 2, 3, 4, 8, 7, 6, 5, 9, 10, 11, 12, 16, 15, 14, 13]);
+        assert_eq!(GoalLayout::Spiral.cells(4), [1, 2, 3, 4, 12, 13, 14, 5, 11, 16, 15, 6, 10, 9, 8, 7]);
+        assert_eq!(GoalLayout::Spiral.cells(3), [1, 2, 3, 8, 9, 4, 7, 6, 5]);
+        assert_eq!(GoalLayout::Snake.cells(2), [1, 2, 4, 3]);
+    }
+
+    #[test]
+    fn name_test() {
+        for goal in &GoalLayout::ALL {
+            assert_eq!(goal.to_string().parse::<GoalLayout>(), Ok(*goal));
+        }
+        assert!("zigzag".parse::<GoalLayout>().is_err());
+        assert_eq!(GoalLayout::Spiral.next(), GoalLayout::Standard);
+    }
+}
@@ -0,0 +1,71 @@
+//! Game in terminal without window: board is printed, moves are read line by line.
+//! Line of letters moves the zero field (`notation` with `Convention::Blank`),
+//! line of numbers names tiles to slide into it. `q` ends the game.
+
+use std::io::{self, BufRead, Write};
+
+use crate::gameboard::Gameboard;
+use crate::notation::{self, Convention};
+
+///Play on `board` until it is solved, input ends or `q` is typed. Returns true if solved
+pub fn play<R: BufRead, W: Write>(board: &mut Gameboard, input: R, mut output: W) -> io::Result<bool> {
+    writeln!(output, "{}", board)?;
+    let mut lines = input.lines();
+    while !board.is_over() {
+        write!(output, "moves: {}> ", board.moves)?;
+        output.flush()?;
+        let line = match lines.next() {
+            Some(line) => line?,
+            None => return Ok(false)
+        };
+        let line = line.trim();
+        if line == "q" {
+            return Ok(false);
+        }
+        //Wrong move cancels the whole line
+        let mut next = board.clone();
+        let result = if line.starts_with(|c: char| c.is_ascii_digit()) {
+            notation::play_tiles(&mut next, line).map(|_| ())
+        } else {
+            notation::play_letters(&mut next, line, Convention::Blank).map(|_| ())
+        };
+        match result {
+            Ok(()) => {
+                *board = next;
+                writeln!(output, "{}", board)?;
+            }
+            Err(e) => writeln!(output, "{}", e)?,
+        }
+    }
+    writeln!(output, "Solved in {} moves", board.moves)?;
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn play_test() {
+        let mut board: Gameboard = "1 2 3/4 5 6/7 0 8".parse().unwrap();
+        let mut output = vec![];
+        assert!(play(&mut board, "L\nR x\n7 8\n".as_bytes(), &mut output).unwrap());
+        assert_eq!(board.moves, 3);
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.contains("move #2 at column 3: unknown symbol 'x'"), "{}", output);
+        assert!(output.ends_with("Solved in 3 moves\n"));
+
+        let mut board: Gameboard = "1 2 3/4 5 6/7 0 8".parse().unwrap();
+        assert!(!play(&mut board, "q\nR\n".as_bytes(), io::sink()).unwrap());
+        assert_eq!(board.moves, 0);
+
+        //Obstacle isn't moved into the zero field
+        let mut board: Gameboard = "1 2 3/4 # 0/6 5 0".parse().unwrap();
+        let mut output = vec![];
+        assert!(!play(&mut board, "L\n0\n".as_bytes(), &mut output).unwrap());
+        assert_eq!(board.moves, 0);
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.contains("zero field can't move Left"), "{}", output);
+        assert!(output.contains("tile 0 isn't next to zero field"), "{}", output);
+    }
+}
@@ -0,0 +1,39 @@
+//! JSON Lines files: one value in a line, every value is appended with one write,
+//! so two games finished at once don't lose or corrupt each other's lines. Broken lines are skipped.
+
+use std::fs::{self, OpenOptions};
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::path::Path;
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+///Values of the file at `path`, no file means no values
+pub fn read<T: DeserializeOwned, P: AsRef<Path>>(path: P) -> io::Result<Vec<T>> {
+    match fs::read_to_string(path) {
+        Ok(text) => Ok(text.lines().filter_map(|line| serde_json::from_str(line).ok()).collect()),
+        Err(ref e) if e.kind() == io::ErrorKind::NotFound => Ok(vec![]),
+        Err(e) => Err(e)
+    }
+}
+
+///Add `value` as the last line of the file at `path`
+pub fn append<T: Serialize, P: AsRef<Path>>(path: P, value: &T) -> io::Result<()> {
+    let path = path.as_ref();
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let mut line = serde_json::to_string(value).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+    line.push('\n');
+    let mut file = OpenOptions::new().create(true).read(true).append(true).open(path)?;
+    //Broken last line must not swallow the new one
+    if file.metadata()?.len() > 0 {
+        let mut last = [0; 1];
+        file.seek(SeekFrom::End(-1))?;
+        file.read_exact(&mut last)?;
+        if last[0] != b'\n' {
+            line.insert(0, '\n');
+        }
+    }
+    file.write_all(line.as_bytes())
+}
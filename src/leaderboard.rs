@@ -0,0 +1,115 @@
+//! Local high scores with player names, stored as JSON Lines.
+
+use std::io;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::jsonl;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Score {
+    pub name: String,
+    ///Width of the board
+    pub size: usize,
+    pub moves: usize,
+    ///Seconds from start to win
+    pub time: f64,
+    ///Unix time of the win
+    pub finished_at: u64,
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum Ranking {
+    FewestMoves,
+    Fastest,
+}
+
+pub struct Leaderboard {
+    path: PathBuf,
+    pub scores: Vec<Score>,
+}
+
+impl Leaderboard {
+    ///Count of scores shown in a table
+    pub const TOP: usize = 5;
+    pub const MAX_NAME: usize = 12;
+
+    ///`leaderboard.jsonl` in user data directory
+    pub fn default_path() -> Option<PathBuf> {
+        dirs::data_dir().map(|dir| dir.join("fifteen").join("leaderboard.jsonl"))
+    }
+
+    ///Read scores from `path`, no file means empty leaderboard
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let scores = jsonl::read(&path)?;
+        Ok(Leaderboard { path, scores })
+    }
+
+    ///Append score to the file
+    pub fn add(&mut self, score: Score) -> io::Result<()> {
+        jsonl::append(&self.path, &score)?;
+        self.scores.push(score);
+        Ok(())
+    }
+
+    ///Best `n` scores for board `size`, earlier score wins a tie
+    pub fn top(&self, size: usize, ranking: Ranking, n: usize) -> Vec<&Score> {
+        let mut scores: Vec<&Score> = self.scores.iter().filter(|s| s.size == size).collect();
+        match ranking {
+            Ranking::FewestMoves => scores.sort_by(|a, b| a.moves.cmp(&b.moves)
+                .then(a.time.total_cmp(&b.time))
+                .then(a.finished_at.cmp(&b.finished_at))),
+            Ranking::Fastest => scores.sort_by(|a, b| a.time.total_cmp(&b.time)
+                .then(a.moves.cmp(&b.moves))
+                .then(a.finished_at.cmp(&b.finished_at))),
+        }
+        scores.truncate(n);
+        scores
+    }
+
+    ///Name of the last score, to offer it again
+    pub fn last_name(&self) -> Option<&str> {
+        self.scores.iter().max_by_key(|s| s.finished_at).map(|s| s.name.as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs::{self, OpenOptions};
+    use std::io::Write;
+
+    fn score(name: &str, moves: usize, time: f64, finished_at: u64) -> Score {
+        Score { name: name.to_owned(), size: 4, moves, time, finished_at }
+    }
+
+    #[test]
+    fn append_test() {
+        let path = std::env::temp_dir().join(format!("fifteen_leaderboard_{}.jsonl", std::process::id()));
+        let _ = fs::remove_file(&path);
+        let mut first = Leaderboard::load(&path).unwrap();
+        let mut second = Leaderboard::load(&path).unwrap();
+        first.add(score("ann", 90, 50.0, 1)).unwrap();
+        second.add(score("bob", 80, 70.0, 2)).unwrap();
+        //Line left by a crash in the middle of write
+        OpenOptions::new().append(true).open(&path).unwrap().write_all(b"{\"name\":\"cr").unwrap();
+        first.add(score("cid", 70, 60.0, 3)).unwrap();
+        let loaded = Leaderboard::load(&path).unwrap();
+        assert_eq!(loaded.scores, vec![score("ann", 90, 50.0, 1), score("bob", 80, 70.0, 2), score("cid", 70, 60.0, 3)]);
+        assert_eq!(loaded.last_name(), Some("cid"));
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn top_test() {
+        let mut board = Leaderboard { path: PathBuf::new(), scores: vec![] };
+        board.scores = vec![score("a", 90, 50.0, 1), score("b", 80, 70.0, 2), score("c", 80, 60.0, 3), score("d", 10, 5.0, 4)];
+        board.scores[3].size = 3;
+        let names = |scores: Vec<&Score>| scores.iter().map(|s| s.name.clone()).collect::<Vec<String>>();
+        assert_eq!(names(board.top(4, Ranking::FewestMoves, 5)), vec!["c", "b", "a"]);
+        assert_eq!(names(board.top(4, Ranking::Fastest, 2)), vec!["a", "c"]);
+        assert_eq!(names(board.top(3, Ranking::Fastest, 2)), vec!["d"]);
+    }
+}
@@ -0,0 +1,44 @@
+//! Use MVC pattern for this APP
+//! Model -> gameboard
+//! View -> gameboard_view
+//! Controller -> gameboard_controller
+//!
+//! Game logic lives in the library, so other tools (puzzle sharing, solvers)
+//! can use the board, replays and move notation without a window.
+//!
+
+extern crate piston;
+extern crate graphics;
+
+#[macro_use]
+mod names;
+pub mod gameboard;
+pub mod gameboard_controller;
+pub mod gameboard_view;
+pub mod animator;
+pub mod replay;
+pub mod notation;
+pub mod goal;
+pub mod stats;
+pub mod leaderboard;
+pub mod jsonl;
+pub mod difficulty;
+pub mod headless;
+pub mod solver;
+pub mod generator;
+pub mod daily;
+pub mod pack;
+pub mod bindings;
+pub mod gamepad;
+pub mod software;
+pub mod svg;
+pub mod recording;
+pub mod tutorial;
+pub mod strategy;
+pub mod topology;
+
+
+pub use gameboard::Gameboard;
+pub use gameboard_controller::GameboardController;
+pub use gameboard_view::{GameboardView, GameboardViewSettings};
+pub use animator::Animator;
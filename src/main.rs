@@ -1,7 +1,4 @@
-//! Use MVC pattern for this APP
-//! Model -> gameboard
-//! View -> gameboard_view
-//! Controller -> gameboard_controller
+//! Window of the game, see library for MVC parts
 //!
 
 extern crate piston;
@@ -9,44 +6,236 @@ extern crate graphics;
 extern crate glutin_window;
 extern crate opengl_graphics;
 
-mod gameboard;
-mod gameboard_controller;
-mod gameboard_view;
-mod animator;
+mod cli;
+mod commands;
 
+use std::fs::File;
+use std::io::{self, BufReader};
+use std::path::Path;
+use std::process;
 
-pub use gameboard::Gameboard;
-pub use gameboard_controller::GameboardController;
-pub use gameboard_view::{GameboardView, GameboardViewSettings};
-pub use animator::Animator;
-
-use piston::window::WindowSettings;
+use clap::Parser;
+use piston_fifteen::{animator, headless, Animator, GameboardController, GameboardView, GameboardViewSettings};
+use piston_fifteen::replay::{Origin, Replay};
+use piston_fifteen::daily;
+use piston_fifteen::stats::{self, StatsStore};
+use piston_fifteen::leaderboard::Leaderboard;
+use piston_fifteen::pack::{Pack, Progress, PACK_DIR};
+use piston_fifteen::bindings::Bindings;
+use piston_fifteen::gamepad::Gamepads;
+use piston_fifteen::software::{self, SoftwareGlyphs, SCREENSHOT_DIR};
+use piston::window::{Window as _, WindowSettings};
 use piston::event_loop::*;
 use piston::input::*;
 use glutin_window::GlutinWindow as Window;
 use opengl_graphics::{OpenGL, Filter, GlGraphics, GlyphCache, TextureSettings};
+
 fn main() {
+    let args = cli::Args::parse();
+    if let Some(command) = &args.command {
+        run_command(command, &args.font);
+        return;
+    }
+    if let Some(path) = &args.replay {
+        let replay = load_replay(path);
+        if args.headless {
+            check_replay(&replay);
+        } else {
+            let mut controller = GameboardController::new(replay.initial(), animator(&args));
+            controller.play(replay);
+            run_window(&args, controller);
+        }
+        return;
+    }
+
+    //Saved game or new one, both continue a replay
+    let day = args.date.unwrap_or_else(stats::today);
+    let replay = match &args.load {
+        Some(path) => load_replay(path),
+        None if args.daily => daily::replay(day),
+        None => {
+            let mut replay = Replay::new(Origin::Seed(args.seed.unwrap_or_else(rand::random)));
+            replay.size = args.size;
+            replay.difficulty = args.difficulty;
+            replay.goal = args.goal;
+            replay.topology = args.topology;
+            replay
+        }
+    };
+    let animator = animator(&args);
+    let mut controller = GameboardController::from_replay(replay, animator)
+        .unwrap_or_else(|e| fail(&e.to_string()));
+    if args.daily {
+        controller.daily = Some(day);
+    }
+    if args.headless {
+        let mut board = controller.gameboard.clone();
+        let stdin = io::stdin();
+        if let Err(e) = headless::play(&mut board, stdin.lock(), io::stdout()) {
+            fail(&e.to_string());
+        }
+    } else {
+        run_window(&args, controller);
+    }
+}
+
+fn run_command(command: &cli::Command, font: &Path) {
+    let result = match command {
+        cli::Command::Solve { input, goal, topology, json, max_nodes } => {
+            let stdout = io::stdout();
+            match input {
+                Some(path) => File::open(path)
+                    .and_then(|file| commands::solve(BufReader::new(file), stdout.lock(), *goal, *topology, *json, *max_nodes)),
+                None => commands::solve(io::stdin().lock(), stdout.lock(), *goal, *topology, *json, *max_nodes),
+            }
+        }
+        cli::Command::Generate { count, size, length, seed, goal, format, max_nodes } => {
+            let options = commands::GenerateOptions {
+                count: *count,
+                size: *size,
+                lengths: length.clone(),
+                seed: seed.unwrap_or_else(rand::random),
+                goal: *goal,
+                format: *format,
+                max_nodes: *max_nodes,
+            };
+            let result = commands::generate(&options, io::stdout().lock());
+            if let Ok(false) = result {
+                eprintln!("error: no board with optimal solution of {}..={} moves was found", length.start(), length.end());
+            }
+            result
+        }
+        cli::Command::Svg { board, solve, replay, animate, seconds, goal, theme, output } => {
+            let options = commands::SvgOptions {
+                board: board.clone(),
+                replay: replay.as_deref().map(load_replay),
+                solve: *solve,
+                animate: *animate,
+                seconds: *seconds,
+                goal: *goal,
+                theme: *theme,
+            };
+            match output {
+                Some(path) => File::create(path).and_then(|file| commands::svg(&options, file)),
+                None => commands::svg(&options, io::stdout().lock()),
+            }
+        }
+        cli::Command::Gif { board, replay, fps, seconds, size, goal, theme, output } => {
+            let options = commands::GifOptions {
+                board: board.clone(),
+                replay: replay.as_deref().map(load_replay),
+                fps: *fps,
+                seconds: *seconds,
+                size: *size,
+                goal: *goal,
+                theme: *theme,
+            };
+            let mut glyphs = software::glyphs(font).unwrap_or_else(|e| fail(&format!("{}: {}", font.display(), e)));
+            File::create(output).and_then(|file| commands::gif(&options, &mut glyphs, io::BufWriter::new(file)))
+        }
+    };
+    match result {
+        Ok(true) => {}
+        Ok(false) => process::exit(1),
+        Err(e) => fail(&e.to_string())
+    }
+}
+
+fn fail(message: &str) -> ! {
+    eprintln!("error: {}", message);
+    process::exit(1)
+}
+
+fn load_replay(path: &Path) -> Replay {
+    Replay::load(path).unwrap_or_else(|e| fail(&format!("{}: {}", path.display(), e)))
+}
+
+///Print result of replay check, exit with error if it doesn't solve the board
+fn check_replay(replay: &Replay) {
+    match replay.verify() {
+        Ok(board) => {
+            println!("{}", board);
+            println!("Solved in {} moves", board.moves);
+        }
+        Err(e) => fail(&e.to_string())
+    }
+}
+
+///Tile slides `--animation-speed` percent of the cell per frame
+fn animator(args: &cli::Args) -> Box<dyn Animator> {
+    Box::new(animator::PlainAnimator::new(100.0, args.animation_speed))
+}
+
+fn run_window(args: &cli::Args, mut gameboard_controller: GameboardController) {
+    if !args.font.is_file() {
+        fail(&format!("font {} is not found", args.font.display()));
+    }
+    let gameboard_view_settings = GameboardViewSettings::with_theme(args.theme);
+    let window_size = gameboard_view_settings.window_size();
     let opengl = OpenGL::V3_2;
-    let settings = WindowSettings::new("Fifteen", [460, 500])
+    let settings = WindowSettings::new("Fifteen", window_size)
         .graphics_api(opengl)
         .resizable(false)
-        .exit_on_esc(true);
+        .exit_on_esc(false);
     let mut window: Window = settings.build()
         .expect("Could not create window");
 
     let mut events = Events::new(EventSettings::new()); //lazy, так как анимации не будет никакой.
     let mut gl = GlGraphics::new(opengl);
     let texture_settings = TextureSettings::new().filter(Filter::Nearest);
-    let glyphs =& mut GlyphCache::new("assets/amazone.ttf", (), texture_settings).expect("Could not load font");
-    let gameboard = Gameboard::new();
-    println!("{}", &gameboard);
-    dbg!(&gameboard);
-    let anima:Box<dyn Animator> = Box::new(animator::PlainAnimator::new(100.0, 10.0));
-    let mut gameboard_controller = GameboardController::new(gameboard, anima);
-    let gameboard_view_settings = GameboardViewSettings::new();
-    let gameboard_view = GameboardView::new(gameboard_view_settings);
+    let glyphs =& mut GlyphCache::new(&args.font, (), texture_settings).expect("Could not load font");
+    gameboard_controller.stats = StatsStore::default_path().and_then(|path| StatsStore::load(path)
+        .map_err(|e| println!("stats weren't loaded: {}", e))
+        .ok());
+    gameboard_controller.leaderboard = Leaderboard::default_path().and_then(|path| Leaderboard::load(path)
+        .map_err(|e| println!("leaderboard wasn't loaded: {}", e))
+        .ok());
+    match Pack::load_dir(PACK_DIR) {
+        Ok(packs) => for pack in packs {
+            match pack {
+                Ok(pack) => gameboard_controller.packs.push(pack),
+                Err((path, e)) => println!("{}: {}", path.display(), e)
+            }
+        },
+        Err(e) => println!("packs weren't loaded: {}", e)
+    }
+    gameboard_controller.progress = Progress::default_path().and_then(|path| Progress::load(path)
+        .map_err(|e| println!("progress wasn't loaded: {}", e))
+        .ok());
+    //Broken bindings file stops the game, so the player sees what is wrong
+    if let Some(path) = args.bindings.clone().or_else(Bindings::default_path) {
+        match Bindings::load(&path) {
+            Ok(bindings) => gameboard_controller.bindings = bindings,
+            Err(e) => fail(&format!("{}: {}", path.display(), e))
+        }
+    }
+    gameboard_controller.theme = args.theme;
+    println!("{}", &gameboard_controller.gameboard);
+    let mut gameboard_view = GameboardView::new(gameboard_view_settings);
+    let mut theme = args.theme;
+    //Font for pictures is loaded with the first one
+    let mut software_glyphs: Option<SoftwareGlyphs> = None;
+    let mut gamepads = Gamepads::new();
     while let Some(e) = events.next(&mut window) {
-        gameboard_controller.event(gameboard_view.settings.position, gameboard_view.settings.size, &e);
+        //Окно не присылает событий геймпада, они читаются на каждом обновлении
+        let pads = if e.update_args().is_some() { gamepads.poll() } else { Vec::new() };
+        for e in std::iter::once(&e).chain(&pads) {
+            if gameboard_view.event(&gameboard_controller, e) {
+                let (position, size) = gameboard_view.board_area();
+                gameboard_controller.event(position, size, e);
+            }
+        }
+        if gameboard_controller.quit {
+            window.set_should_close(true);
+        }
+        if gameboard_controller.screenshot {
+            gameboard_controller.screenshot = false;
+            screenshot(&gameboard_view, &mut gameboard_controller, &mut software_glyphs, &args.font);
+        }
+        if gameboard_controller.theme != theme {
+            theme = gameboard_controller.theme;
+            gameboard_view.settings = GameboardViewSettings::with_theme(theme);
+        }
         if let Some(args) = e.render_args() {
             gl.draw(args.viewport(), |c, g| {
                 use graphics::clear;
@@ -56,4 +245,18 @@ fn main() {
             });
         }
     }
-}
\ No newline at end of file
+}
+
+///Save picture of the screen into screenshots directory
+fn screenshot(view: &GameboardView, controller: &mut GameboardController, glyphs: &mut Option<SoftwareGlyphs>, font: &Path) {
+    if glyphs.is_none() {
+        *glyphs = software::glyphs(font).map_err(|e| println!("font wasn't loaded: {}", e)).ok();
+    }
+    if let Some(glyphs) = glyphs {
+        let path = Path::new(SCREENSHOT_DIR).join(format!("{}.png", stats::now()));
+        match view.export_png(controller, glyphs, &path) {
+            Ok(()) => println!("picture saved to {}", path.display()),
+            Err(e) => println!("picture wasn't saved: {}", e)
+        }
+    }
+}
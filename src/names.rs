@@ -0,0 +1,29 @@
+//! Names of option enums for the command line, config and replay files.
+
+///`Display` and `FromStr` of enum by the table of its variant names, `what` names the enum in the parse error:
+///```text
+///names!(Topology, "topology", { Flat => "flat", Torus => "torus" });
+///```
+macro_rules! names {
+    ($type:ident, $what:expr, { $($variant:ident => $name:expr),+ $(,)? }) => {
+        impl std::fmt::Display for $type {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                let name = match self {
+                    $($type::$variant => $name,)+
+                };
+                write!(f, "{}", name)
+            }
+        }
+
+        impl std::str::FromStr for $type {
+            type Err = String;
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                match s.trim() {
+                    $($name => Ok($type::$variant),)+
+                    _ => Err(format!("unknown {} '{}'", $what, s))
+                }
+            }
+        }
+    };
+}
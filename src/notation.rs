@@ -0,0 +1,230 @@
+//! Move notation for sharing solutions.
+//! Letters `U`, `D`, `L`, `R` are read by `Convention`: as moves of the zero field
+//! (common convention, `R` - zero goes right) or as moves of tiles (`R` - tile slides right into zero).
+//! Tile lists name the tile moved into zero on each step: `"12 8 4 3"`.
+
+use std::error::Error;
+use std::fmt;
+
+use crate::animator::Direction;
+use crate::gameboard::Gameboard;
+
+///Meaning of move letters
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum Convention {
+    ///Letter is a direction where zero field goes
+    Blank,
+    ///Letter is a direction where tile slides
+    Tile,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum NotationErrorKind {
+    UnknownSymbol(char),
+    BadNumber(String),
+    ///Zero field can't go this way from the board edge
+    IllegalMove(Direction),
+    ///Tile isn't next to zero field
+    NotNeighbour(u16),
+}
+
+///Error with place in the text: char offset and number of the move
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct NotationError {
+    pub offset: usize,
+    pub index: usize,
+    pub kind: NotationErrorKind,
+}
+
+impl fmt::Display for NotationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "move #{} at column {}: ", self.index + 1, self.offset + 1)?;
+        match &self.kind {
+            NotationErrorKind::UnknownSymbol(c) => write!(f, "unknown symbol '{}'", c),
+            NotationErrorKind::BadNumber(n) => write!(f, "bad tile number '{}'", n),
+            NotationErrorKind::IllegalMove(d) => write!(f, "zero field can't move {:?}", d),
+            NotationErrorKind::NotNeighbour(t) => write!(f, "tile {} isn't next to zero field", t),
+        }
+    }
+}
+
+impl Error for NotationError {}
+
+impl Convention {
+    ///Letter of zero field move `direction`
+    pub fn letter(self, direction: Direction) -> char {
+        let direction = match self {
+            Convention::Blank => direction,
+            Convention::Tile => direction.opposite(),
+        };
+        match direction {
+            Direction::Top => 'U',
+            Direction::Right => 'R',
+            Direction::Bottom => 'D',
+            Direction::Left => 'L',
+        }
+    }
+
+    ///Zero field move of `letter`, lowercase is accepted
+    pub fn direction(self, letter: char) -> Option<Direction> {
+        let direction = match letter.to_ascii_uppercase() {
+            'U' => Direction::Top,
+            'R' => Direction::Right,
+            'D' => Direction::Bottom,
+            'L' => Direction::Left,
+            _ => return None
+        };
+        match self {
+            Convention::Blank => Some(direction),
+            Convention::Tile => Some(direction.opposite()),
+        }
+    }
+}
+
+///Zero field moves from letters, whitespace and commas are skipped
+pub fn parse_letters(text: &str, convention: Convention) -> Result<Vec<Direction>, NotationError> {
+    Ok(letters_with_offsets(text, convention)?.into_iter().map(|(_, d)| d).collect())
+}
+
+pub fn format_letters(directions: &[Direction], convention: Convention) -> String {
+    directions.iter().map(|d| convention.letter(*d)).collect()
+}
+
+///Tile numbers separated by whitespace or commas
+pub fn parse_tiles(text: &str) -> Result<Vec<u16>, NotationError> {
+    Ok(tiles_with_offsets(text)?.into_iter().map(|(_, t)| t).collect())
+}
+
+pub fn format_tiles(tiles: &[u16]) -> String {
+    tiles.iter().map(|t| t.to_string()).collect::<Vec<String>>().join(" ")
+}
+
+///Play letters on `board`. Returns tiles moved into zero field
+pub fn play_letters(board: &mut Gameboard, text: &str, convention: Convention) -> Result<Vec<u16>, NotationError> {
+    let mut tiles = vec![];
+    for (index, (offset, direction)) in letters_with_offsets(text, convention)?.into_iter().enumerate() {
+        let zero = board.zero();
+        //Edge, obstacle or another zero field can't go into the zero field
+        let tile = board.neighbour(zero, direction)
+            .filter(|position| board.can_slide(*position, zero))
+            .map(|position| board[position])
+            .ok_or(NotationError { offset, index, kind: NotationErrorKind::IllegalMove(direction) })?;
+        board.move_zero(direction);
+        tiles.push(tile);
+    }
+    Ok(tiles)
+}
+
+///Play tile list on `board`. Returns zero field moves
+pub fn play_tiles(board: &mut Gameboard, text: &str) -> Result<Vec<Direction>, NotationError> {
+    let mut directions = vec![];
+    for (index, (offset, tile)) in tiles_with_offsets(text)?.into_iter().enumerate() {
+        let zero = board.zero();
+        let direction = Direction::ALL.iter()
+            .copied()
+            .find(|d| board.neighbour(zero, *d).is_some_and(|position| board[position] == tile && board.can_slide(position, zero)))
+            .ok_or(NotationError { offset, index, kind: NotationErrorKind::NotNeighbour(tile) })?;
+        board.move_zero(direction);
+        directions.push(direction);
+    }
+    Ok(directions)
+}
+
+fn letters_with_offsets(text: &str, convention: Convention) -> Result<Vec<(usize, Direction)>, NotationError> {
+    let mut moves = vec![];
+    for (offset, c) in text.chars().enumerate() {
+        if c.is_whitespace() || c == ',' {
+            continue;
+        }
+        match convention.direction(c) {
+            Some(direction) => moves.push((offset, direction)),
+            None => return Err(NotationError { offset, index: moves.len(), kind: NotationErrorKind::UnknownSymbol(c) })
+        }
+    }
+    Ok(moves)
+}
+
+fn tiles_with_offsets(text: &str) -> Result<Vec<(usize, u16)>, NotationError> {
+    let mut tiles = vec![];
+    let mut number = String::new();
+    let mut start = 0;
+    for (offset, c) in text.chars().chain(std::iter::once(' ')).enumerate() {
+        if c.is_whitespace() || c == ',' {
+            if !number.is_empty() {
+                let tile = number.parse::<u16>().map_err(|_| NotationError {
+                    offset: start, index: tiles.len(), kind: NotationErrorKind::BadNumber(number.clone()),
+                })?;
+                tiles.push((start, tile));
+                number.clear();
+            }
+        } else if c.is_ascii_digit() {
+            if number.is_empty() {
+                start = offset;
+            }
+            number.push(c);
+        } else {
+            return Err(NotationError { offset, index: tiles.len(), kind: NotationErrorKind::UnknownSymbol(c) });
+        }
+    }
+    Ok(tiles)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn solved() -> Gameboard {
+        Gameboard::from_rows(4, &(1..=16).collect::<Vec<u16>>())
+    }
+
+    #[test]
+    fn convention_test() {
+        assert_eq!(parse_letters("ul", Convention::Blank).unwrap(), vec![Direction::Top, Direction::Left]);
+        assert_eq!(parse_letters("D R", Convention::Tile).unwrap(), vec![Direction::Top, Direction::Left]);
+        assert_eq!(format_letters(&[Direction::Top, Direction::Left], Convention::Tile), "DR");
+    }
+
+    #[test]
+    fn round_trip_test() {
+        let mut board = solved();
+        let tiles = play_letters(&mut board, "UULDR", Convention::Blank).unwrap();
+        assert_eq!(format_tiles(&tiles), "12 8 7 11 8");
+        let after_letters = board.clone();
+
+        let mut board = solved();
+        let directions = play_tiles(&mut board, "12 8 7 11 8").unwrap();
+        assert_eq!(format_letters(&directions, Convention::Blank), "UULDR");
+        assert_eq!(board.cells, after_letters.cells);
+    }
+
+    #[test]
+    fn illegal_move_test() {
+        let mut board = solved();
+        let e = play_letters(&mut board, "U R", Convention::Blank).unwrap_err();
+        assert_eq!(e, NotationError { offset: 2, index: 1, kind: NotationErrorKind::IllegalMove(Direction::Right) });
+        let e = play_tiles(&mut solved(), "12 8 5").unwrap_err();
+        assert_eq!(e, NotationError { offset: 5, index: 2, kind: NotationErrorKind::NotNeighbour(5) });
+        assert_eq!(e.to_string(), "move #3 at column 6: tile 5 isn't next to zero field");
+    }
+
+    #[test]
+    fn designer_test() {
+        //Obstacle left of the first zero field, the second zero field under it
+        let board: Gameboard = "1 2 3/4 # 0/6 5 0".parse().unwrap();
+        let e = play_letters(&mut board.clone(), "L", Convention::Blank).unwrap_err();
+        assert_eq!(e.kind, NotationErrorKind::IllegalMove(Direction::Left));
+        let e = play_letters(&mut board.clone(), "D", Convention::Blank).unwrap_err();
+        assert_eq!(e.kind, NotationErrorKind::IllegalMove(Direction::Bottom));
+        assert_eq!(play_tiles(&mut board.clone(), "0").unwrap_err().kind, NotationErrorKind::NotNeighbour(0));
+        let mut played = board.clone();
+        assert_eq!(play_letters(&mut played, "U", Convention::Blank).unwrap(), vec![3]);
+        assert_eq!(played.moves, 1);
+    }
+
+    #[test]
+    fn parse_errors_test() {
+        assert_eq!(parse_letters("UDX", Convention::Blank).unwrap_err().kind, NotationErrorKind::UnknownSymbol('X'));
+        assert_eq!(parse_tiles("1 70000").unwrap_err().offset, 2);
+        assert_eq!(parse_tiles("1, 2,3").unwrap(), vec![1, 2, 3]);
+    }
+}
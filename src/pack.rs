@@ -0,0 +1,213 @@
+//! Puzzle packs: JSON files in `assets/packs` with named boards.
+//! ```json
+//! {"name": "Beginner", "levels": [{"name": "First", "size": 3, "cells": "1 2 3/4 5 6/7 0 8", "par": 1}]}
+//! ```
+//! `cells` is any board text format, `par` may be left out.
+//! Level is unlocked when the level before it is solved.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::gameboard::Gameboard;
+
+/// Directory with pack files
+pub const PACK_DIR: &str = "assets/packs";
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Level {
+    pub name: String,
+    ///Width of the board
+    pub size: usize,
+    ///Initial board as text
+    pub cells: String,
+    ///Moves to solve the level well
+    #[serde(default)]
+    pub par: Option<usize>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Pack {
+    ///Name of the file without extension, progress is stored by it
+    #[serde(skip)]
+    pub id: String,
+    pub name: String,
+    pub levels: Vec<Level>,
+}
+
+#[derive(Debug)]
+pub enum PackError {
+    Io(io::Error),
+    Json(serde_json::Error),
+    ///Level with this 0-biased index is wrong
+    Level { level: usize, message: String },
+}
+
+impl fmt::Display for PackError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PackError::Io(e) => write!(f, "pack io error: {}", e),
+            PackError::Json(e) => write!(f, "pack format error: {}", e),
+            PackError::Level { level, message } => write!(f, "level {}: {}", level + 1, message),
+        }
+    }
+}
+
+impl Error for PackError {}
+
+impl From<io::Error> for PackError {
+    fn from(e: io::Error) -> Self {
+        PackError::Io(e)
+    }
+}
+
+impl From<serde_json::Error> for PackError {
+    fn from(e: serde_json::Error) -> Self {
+        PackError::Json(e)
+    }
+}
+
+impl Level {
+    ///Initial board of the level
+    pub fn board(&self) -> Result<Gameboard, String> {
+        let board: Gameboard = self.cells.parse().map_err(|e| format!("{}", e))?;
+        if board.size != self.size {
+            return Err(format!("board is {0}x{0}, but size is {1}", board.size, self.size));
+        }
+        if !board.is_solvable() {
+            return Err("board can't be solved".to_owned());
+        }
+        Ok(board)
+    }
+}
+
+impl Pack {
+    ///Read pack and check all its levels
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, PackError> {
+        let path = path.as_ref();
+        let mut pack: Pack = serde_json::from_str(&fs::read_to_string(path)?)?;
+        pack.id = path.file_stem().map(|s| s.to_string_lossy().into_owned()).unwrap_or_default();
+        for (level, l) in pack.levels.iter().enumerate() {
+            l.board().map_err(|message| PackError::Level { level, message })?;
+        }
+        Ok(pack)
+    }
+
+    ///All `.json` packs of `dir` by file name, with errors of broken ones
+    pub fn load_dir<P: AsRef<Path>>(dir: P) -> io::Result<Vec<Result<Pack, (PathBuf, PackError)>>> {
+        let mut paths: Vec<PathBuf> = fs::read_dir(dir)?
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|path| path.extension().is_some_and(|e| e == "json"))
+            .collect();
+        paths.sort();
+        Ok(paths.into_iter().map(|path| Pack::load(&path).map_err(|e| (path, e))).collect())
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct ProgressFile {
+    completed: BTreeMap<String, BTreeSet<usize>>,
+}
+
+///Solved levels of all packs, stored as JSON in user data directory
+pub struct Progress {
+    path: PathBuf,
+    ///Indexes of solved levels by pack id
+    pub completed: BTreeMap<String, BTreeSet<usize>>,
+}
+
+impl Progress {
+    ///`progress.json` in user data directory
+    pub fn default_path() -> Option<PathBuf> {
+        dirs::data_dir().map(|dir| dir.join("fifteen").join("progress.json"))
+    }
+
+    ///Read progress from `path`, no file means nothing is solved
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let file = match fs::read_to_string(&path) {
+            Ok(text) => serde_json::from_str(&text).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?,
+            Err(ref e) if e.kind() == io::ErrorKind::NotFound => ProgressFile::default(),
+            Err(e) => return Err(e)
+        };
+        Ok(Progress { path, completed: file.completed })
+    }
+
+    ///Write into temporary file and rename it, as stats do
+    pub fn save(&self) -> io::Result<()> {
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let file = ProgressFile { completed: self.completed.clone() };
+        let text = serde_json::to_string_pretty(&file).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        let temporary = self.path.with_extension("json.tmp");
+        fs::write(&temporary, text)?;
+        fs::rename(&temporary, &self.path)
+    }
+
+    ///Mark level solved and save
+    pub fn complete(&mut self, pack: &str, level: usize) -> io::Result<()> {
+        self.completed.entry(pack.to_owned()).or_default().insert(level);
+        self.save()
+    }
+
+    pub fn is_completed(&self, pack: &str, level: usize) -> bool {
+        self.completed.get(pack).is_some_and(|levels| levels.contains(&level))
+    }
+
+    ///First level and levels after solved ones can be played
+    pub fn is_unlocked(&self, pack: &str, level: usize) -> bool {
+        level == 0 || self.is_completed(pack, level - 1) || self.is_completed(pack, level)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn load_test() {
+        let dir = std::env::temp_dir().join(format!("fifteen_packs_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("b.json"), r#"{"name": "B", "levels": [
+            {"name": "One", "size": 3, "cells": "1 2 3/4 5 6/7 0 8", "par": 1},
+            {"name": "Two", "size": 2, "cells": "1 2 0 3"}]}"#).unwrap();
+        fs::write(dir.join("a.json"), r#"{"name": "A", "levels": [{"name": "Bad", "size": 3, "cells": "1 2 3/4 5 6/8 7 0"}]}"#).unwrap();
+        fs::write(dir.join("notes.txt"), "not a pack").unwrap();
+        let packs = Pack::load_dir(&dir).unwrap();
+        assert_eq!(packs.len(), 2);
+        match &packs[0] {
+            Err((_, PackError::Level { level: 0, message })) => assert_eq!(message, "board can't be solved"),
+            other => panic!("{:?}", other),
+        }
+        let pack = packs[1].as_ref().unwrap();
+        assert_eq!(pack.id, "b");
+        assert_eq!(pack.levels[0].par, Some(1));
+        assert_eq!(pack.levels[1].board().unwrap().size, 2);
+        fs::remove_dir_all(&dir).unwrap();
+
+        let beginner = Pack::load(Path::new(PACK_DIR).join("beginner.json")).unwrap();
+        assert!(beginner.levels.iter().all(|l| l.par.is_some()));
+    }
+
+    #[test]
+    fn progress_test() {
+        let path = std::env::temp_dir().join(format!("fifteen_progress_{}.json", std::process::id()));
+        let _ = fs::remove_file(&path);
+        let mut progress = Progress::load(&path).unwrap();
+        assert!(progress.is_unlocked("b", 0));
+        assert!(!progress.is_unlocked("b", 1));
+        progress.complete("b", 0).unwrap();
+        let progress = Progress::load(&path).unwrap();
+        assert!(progress.is_completed("b", 0));
+        assert!(progress.is_unlocked("b", 1));
+        assert!(!progress.is_unlocked("b", 2));
+        assert!(!progress.is_unlocked("a", 1));
+        fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,132 @@
+//! Animated GIF of a replay. Every frame is drawn by the view with the software backend,
+//! sliding tiles are tween frames of the animator as in the window.
+
+use std::io::{self, Write};
+
+use gif::{Encoder, Frame, Repeat, SetParameter};
+use graphics::character::CharacterCache;
+use image::RgbaImage;
+use piston::input::{Event, Loop, UpdateArgs};
+
+use crate::animator::PlainAnimator;
+use crate::gameboard_controller::GameboardController;
+use crate::gameboard_view::GameboardView;
+use crate::replay::{Replay, ReplayError};
+use crate::software::SoftwareTexture;
+
+///Part of every move the tile slides, the rest it stays
+const SLIDE: f64 = 0.6;
+///Last frame is shown this long before the GIF starts again
+const HOLD_SECONDS: f64 = 1.5;
+
+///Draw frames of `replay` at `fps` frames per second with one move every `seconds`, recorded times are not used.
+///Every frame goes to `frame` as soon as it is drawn, the first one is the initial board, the last one is the board after all moves
+pub fn frames<C, F>(replay: &Replay, view: &GameboardView, glyphs: &mut C, fps: f64, seconds: f64, mut frame: F) -> Result<(), ReplayError>
+    where C: CharacterCache<Texture=SoftwareTexture>, F: FnMut(RgbaImage) -> io::Result<()> {
+    replay.board_at(replay.moves.len())?;
+    let mut replay = replay.clone();
+    for (i, m) in replay.moves.iter_mut().enumerate() {
+        m.at = ((i + 1) as f64 * seconds * 1000.0).round() as u64;
+    }
+    let slide_frames = (fps * seconds * SLIDE).round().max(1.0);
+    let mut controller = GameboardController::new(replay.initial(), Box::new(PlainAnimator::new(100.0, 100.0 / slide_frames)));
+    controller.play(replay);
+    let (position, size) = view.board_area();
+    let update = Event::Loop(Loop::Update(UpdateArgs { dt: 1.0 / fps }));
+    frame(view.render(&mut controller, glyphs))?;
+    while !(controller.player.as_ref().is_some_and(|p| p.is_finished()) && controller.animator.is_over()) {
+        controller.event(position, size, &update);
+        frame(view.render(&mut controller, glyphs))?;
+    }
+    Ok(())
+}
+
+///Endless GIF written frame by frame, only the last frame is kept to hold it longer
+pub struct GifEncoder<W: Write> {
+    encoder: Encoder<W>,
+    fps: f64,
+    ///Count of written frames
+    written: usize,
+    last: Option<RgbaImage>,
+}
+
+impl<W: Write> GifEncoder<W> {
+    ///GIF of frames with `width` and `height` shown at `fps` frames per second
+    pub fn new(output: W, width: u32, height: u32, fps: f64) -> io::Result<Self> {
+        let mut encoder = Encoder::new(output, width as u16, height as u16, &[])?;
+        encoder.set(Repeat::Infinite)?;
+        Ok(GifEncoder { encoder, fps, written: 0, last: None })
+    }
+
+    ///Add the next frame, the one before it is written
+    pub fn push(&mut self, image: RgbaImage) -> io::Result<()> {
+        match self.last.replace(image) {
+            Some(previous) => {
+                //Delays are in 1/100 s, rounding of every frame doesn't add up
+                let hundredths = |i: usize| (i as f64 * 100.0 / self.fps).round() as u16;
+                let delay = hundredths(self.written + 1) - hundredths(self.written);
+                self.write(previous, delay)
+            }
+            None => Ok(())
+        }
+    }
+
+    ///Write the last frame, the end of the file is written when the encoder is dropped
+    pub fn finish(mut self) -> io::Result<()> {
+        match self.last.take() {
+            Some(last) => self.write(last, (HOLD_SECONDS * 100.0) as u16),
+            None => Ok(())
+        }
+    }
+
+    fn write(&mut self, image: RgbaImage, delay: u16) -> io::Result<()> {
+        let (width, height) = image.dimensions();
+        let mut pixels = image.into_raw();
+        let mut frame = Frame::from_rgba_speed(width as u16, height as u16, &mut pixels, 10);
+        frame.delay = delay;
+        self.encoder.write_frame(&frame)?;
+        self.written += 1;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::animator::Direction;
+    use crate::gameboard_view::GameboardViewSettings;
+    use crate::replay::Origin;
+    use crate::software;
+
+    #[test]
+    fn gif_test() {
+        let mut glyphs = software::glyphs("assets/amazone.ttf").unwrap();
+        let view = GameboardView::new(GameboardViewSettings { size: 80.0, ..GameboardViewSettings::new() });
+        let mut replay = Replay::new(Origin::Board("1 2/0 3".parse().unwrap()));
+        replay.record(Direction::Right, 0);
+        //Move is due on the 5th frame and slides 3 frames
+        let mut all = vec![];
+        frames(&replay, &view, &mut glyphs, 10.0, 0.5, |frame| { all.push(frame); Ok(()) }).unwrap();
+        let frames = all;
+        assert_eq!(frames.len(), 1 + 4 + 3);
+        assert!(frames[3].pixels().eq(frames[4].pixels()));
+        assert!(frames[4].pixels().ne(frames[5].pixels()));
+        assert_eq!(frames[0].dimensions(), (100, 140));
+
+        let mut output = vec![];
+        let mut encoder = GifEncoder::new(&mut output, 100, 140, 10.0).unwrap();
+        for frame in frames {
+            encoder.push(frame).unwrap();
+        }
+        assert_eq!(encoder.written, 7);
+        encoder.finish().unwrap();
+        assert!(output.starts_with(b"GIF89a"));
+        assert_eq!(output.last(), Some(&0x3b));
+
+        replay.record(Direction::Right, 0);
+        let mut count = 0;
+        let result = super::frames(&replay, &view, &mut glyphs, 10.0, 0.5, |_| { count += 1; Ok(()) });
+        assert!(matches!(result, Err(ReplayError::IllegalMove(1))));
+        assert_eq!(count, 0);
+    }
+}
@@ -0,0 +1,483 @@
+//! Replay of one game: how the board was made and every move with its time.
+//! Stored as one line of text: `seed=42; moves=RRDLU; times=0,310,705,980,1200`,
+//! games with not standard goal have `goal=snake` after the seed, games on torus have `topology=torus`,
+//! seeded boards not 4x4 or not fully shuffled have `size=5` and `difficulty=easy`.
+//! Moves use `notation` letters of the zero field (`Convention::Blank`),
+//! times are milliseconds from the game start. Boards with several zero fields have the number of the moved one
+//! before the letter if it isn't the first: `R2DL` - the second zero field in row-major order goes down.
+//! Obstacles of such boards are `#` in cells.
+
+use std::error::Error;
+use std::fmt;
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::animator::Direction;
+use crate::difficulty::Difficulty;
+use crate::gameboard::{BoardFormat, Gameboard, DEFAULT_SIZE, MAX_SIZE, MIN_SIZE};
+use crate::goal::GoalLayout;
+use crate::notation::Convention;
+use crate::topology::Topology;
+
+/// Directory for recorded games
+pub const REPLAY_DIR: &str = "replays";
+/// Directory for unfinished games, they are replays too
+pub const SAVE_DIR: &str = "saves";
+
+/// How the initial board of the game was made
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum Origin {
+    Seed(u64),
+    Board(Gameboard),
+}
+
+/// Move of zero field at `at` milliseconds from the game start
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct ReplayMove {
+    ///Index of the moved zero field in `Gameboard::zeros` before the move, always 0 on classic boards
+    pub blank: usize,
+    pub direction: Direction,
+    pub at: u64,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Replay {
+    pub origin: Origin,
+    ///Width of the board made from the seed
+    pub size: usize,
+    ///How the board was scrambled from the seed
+    pub difficulty: Difficulty,
+    pub goal: GoalLayout,
+    pub topology: Topology,
+    pub moves: Vec<ReplayMove>,
+}
+
+#[derive(Debug)]
+pub enum ReplayError {
+    Io(io::Error),
+    Parse(String),
+    ///Move with this index can't be done from the board before it
+    IllegalMove(usize),
+    ///All moves done, but board isn't in order
+    NotSolved,
+}
+
+impl fmt::Display for ReplayError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReplayError::Io(e) => write!(f, "replay io error: {}", e),
+            ReplayError::Parse(e) => write!(f, "replay parse error: {}", e),
+            ReplayError::IllegalMove(i) => write!(f, "illegal move #{} in replay", i + 1),
+            ReplayError::NotSolved => write!(f, "replay doesn't end with solved board"),
+        }
+    }
+}
+
+impl Error for ReplayError {}
+
+impl From<io::Error> for ReplayError {
+    fn from(e: io::Error) -> Self {
+        ReplayError::Io(e)
+    }
+}
+
+impl Replay {
+    pub fn new(origin: Origin) -> Self {
+        let (size, goal, topology) = match &origin {
+            Origin::Seed(_) => (DEFAULT_SIZE, GoalLayout::Standard, Topology::Flat),
+            Origin::Board(board) => (board.size, board.goal, board.topology),
+        };
+        Replay { origin, size, difficulty: Difficulty::default(), goal, topology, moves: vec![] }
+    }
+
+    ///Remember move of zero field
+    pub fn record(&mut self, direction: Direction, at: u64) {
+        self.record_blank(0, direction, at);
+    }
+
+    ///Remember move of zero field with index `blank`
+    pub fn record_blank(&mut self, blank: usize, direction: Direction, at: u64) {
+        self.moves.push(ReplayMove { blank, direction, at });
+    }
+
+    ///Board before the first move
+    pub fn initial(&self) -> Gameboard {
+        match &self.origin {
+            //Boards solvable on flat board are solvable on torus as well
+            Origin::Seed(seed) => Gameboard { topology: self.topology, ..Gameboard::generate(*seed, self.size, self.goal, self.difficulty) },
+            Origin::Board(board) => Gameboard { moves: 0, goal: self.goal, topology: self.topology, ..board.clone() },
+        }
+    }
+
+    ///Board after first `count` moves
+    pub fn board_at(&self, count: usize) -> Result<Gameboard, ReplayError> {
+        let mut board = self.initial();
+        for (i, m) in self.moves.iter().take(count).enumerate() {
+            let zero = board.zeros().get(m.blank).copied();
+            if !zero.is_some_and(|zero| board.move_blank(zero, m.direction)) {
+                return Err(ReplayError::IllegalMove(i));
+            }
+        }
+        Ok(board)
+    }
+
+    ///Check that every move is legal and the game is over after the last one
+    pub fn verify(&self) -> Result<Gameboard, ReplayError> {
+        let board = self.board_at(self.moves.len())?;
+        if board.is_over() {
+            Ok(board)
+        } else {
+            Err(ReplayError::NotSolved)
+        }
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        fs::write(path, format!("{}\n", self))
+    }
+
+    ///Save into `dir` with name from current time, games of the same second get numbers after it
+    pub fn save_in<P: AsRef<Path>>(&self, dir: P) -> io::Result<PathBuf> {
+        fs::create_dir_all(&dir)?;
+        let secs = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+        for n in 0.. {
+            let name = if n == 0 { format!("{}.replay", secs) } else { format!("{}-{}.replay", secs, n) };
+            let path = dir.as_ref().join(name);
+            //Existing file is never overwritten, even by another instance of the game
+            match OpenOptions::new().write(true).create_new(true).open(&path) {
+                Ok(mut file) => {
+                    file.write_all(format!("{}\n", self).as_bytes())?;
+                    return Ok(path);
+                }
+                Err(ref e) if e.kind() == io::ErrorKind::AlreadyExists => {}
+                Err(e) => return Err(e)
+            }
+        }
+        unreachable!()
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, ReplayError> {
+        fs::read_to_string(path)?.parse()
+    }
+}
+
+impl fmt::Display for Replay {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.origin {
+            Origin::Seed(seed) => {
+                write!(f, "seed={}", seed)?;
+                if self.size != DEFAULT_SIZE {
+                    write!(f, "; size={}", self.size)?;
+                }
+                if self.difficulty != Difficulty::default() {
+                    write!(f, "; difficulty={}", self.difficulty)?;
+                }
+            }
+            Origin::Board(board) => {
+                write!(f, "cells={}", board.export(BoardFormat::Flat).replace(' ', ","))?
+            }
+        }
+        if self.goal != GoalLayout::Standard {
+            write!(f, "; goal={}", self.goal)?;
+        }
+        if self.topology != Topology::Flat {
+            write!(f, "; topology={}", self.topology)?;
+        }
+        let moves: String = self.moves.iter()
+            .map(|m| match m.blank {
+                0 => Convention::Blank.letter(m.direction).to_string(),
+                blank => format!("{}{}", blank + 1, Convention::Blank.letter(m.direction)),
+            })
+            .collect();
+        let times: Vec<String> = self.moves.iter().map(|m| m.at.to_string()).collect();
+        write!(f, "; moves={}; times={}", moves, times.join(","))
+    }
+}
+
+impl FromStr for Replay {
+    type Err = ReplayError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut origin = None;
+        let mut size = DEFAULT_SIZE;
+        let mut difficulty = Difficulty::default();
+        let mut goal = GoalLayout::Standard;
+        let mut topology = Topology::Flat;
+        let mut directions = vec![];
+        let mut times = vec![];
+        for part in s.trim().split(';').map(str::trim).filter(|p| !p.is_empty()) {
+            let (key, value) = match part.find('=') {
+                Some(i) => (&part[..i], &part[i + 1..]),
+                None => return Err(ReplayError::Parse(format!("expected key=value, found '{}'", part)))
+            };
+            match key.trim() {
+                "seed" => {
+                    let seed = value.trim().parse()
+                        .map_err(|e| ReplayError::Parse(format!("seed: {}", e)))?;
+                    origin = Some(Origin::Seed(seed));
+                }
+                "cells" => {
+                    let board = value.parse::<Gameboard>()
+                        .map_err(|e| ReplayError::Parse(format!("cells: {}", e)))?;
+                    origin = Some(Origin::Board(board));
+                }
+                "size" => {
+                    size = value.trim().parse()
+                        .map_err(|e| ReplayError::Parse(format!("size: {}", e)))?;
+                    if !(MIN_SIZE..=MAX_SIZE).contains(&size) {
+                        return Err(ReplayError::Parse(format!("size: {} is not in {}..={}", size, MIN_SIZE, MAX_SIZE)));
+                    }
+                }
+                "difficulty" => difficulty = value.parse().map_err(ReplayError::Parse)?,
+                "goal" => goal = value.parse().map_err(ReplayError::Parse)?,
+                "topology" => topology = value.parse().map_err(ReplayError::Parse)?,
+                "moves" => directions = parse_moves(value).map_err(|e| ReplayError::Parse(format!("moves: {}", e)))?,
+                "times" => {
+                    times = value.split(',').map(str::trim).filter(|t| !t.is_empty())
+                        .map(|t| t.parse::<u64>())
+                        .collect::<Result<_, _>>()
+                        .map_err(|e| ReplayError::Parse(format!("times: {}", e)))?;
+                }
+                other => return Err(ReplayError::Parse(format!("unknown key '{}'", other)))
+            }
+        }
+        let origin = origin.ok_or_else(|| ReplayError::Parse("seed or cells expected".to_owned()))?;
+        if times.is_empty() {
+            times = vec![0; directions.len()];
+        }
+        if times.len() != directions.len() {
+            return Err(ReplayError::Parse(format!("{} moves, but {} times", directions.len(), times.len())));
+        }
+        let moves = directions.into_iter().zip(times)
+            .map(|((blank, direction), at)| ReplayMove { blank, direction, at })
+            .collect();
+        if let Origin::Board(board) = &origin {
+            size = board.size;
+        }
+        Ok(Replay { origin, size, difficulty, goal, topology, moves })
+    }
+}
+
+///Letters of zero field moves, with numbers of zero fields from 1 if they aren't the first one
+fn parse_moves(text: &str) -> Result<Vec<(usize, Direction)>, String> {
+    let mut moves = vec![];
+    let mut number = String::new();
+    for c in text.chars().filter(|c| !c.is_whitespace() && *c != ',') {
+        if c.is_ascii_digit() {
+            number.push(c);
+            continue;
+        }
+        let direction = Convention::Blank.direction(c).ok_or_else(|| format!("unknown symbol '{}'", c))?;
+        let blank = match number.as_str() {
+            "" => 0,
+            n => n.parse::<usize>().ok().filter(|n| *n > 0).ok_or_else(|| format!("bad zero field number '{}'", n))? - 1,
+        };
+        number.clear();
+        moves.push((blank, direction));
+    }
+    if !number.is_empty() {
+        return Err(format!("move is expected after '{}'", number));
+    }
+    Ok(moves)
+}
+
+///Plays replay move by move with pause, seek and speed
+pub struct ReplayPlayer {
+    pub replay: Replay,
+    ///Count of already done moves
+    pub position: usize,
+    ///Replay time in milliseconds
+    pub clock: f64,
+    pub speed: f64,
+    pub paused: bool,
+    ///Result of `is_over` after the last move, None while playing
+    pub verified: Option<bool>,
+}
+
+impl ReplayPlayer {
+    pub const MIN_SPEED: f64 = 0.25;
+    pub const MAX_SPEED: f64 = 16.0;
+
+    pub fn new(replay: Replay) -> Self {
+        ReplayPlayer { replay, position: 0, clock: 0.0, speed: 1.0, paused: false, verified: None }
+    }
+
+    ///Move replay time forward by `dt` seconds
+    pub fn tick(&mut self, dt: f64) {
+        if !self.paused && !self.is_finished() {
+            self.clock += dt * 1000.0 * self.speed;
+        }
+    }
+
+    ///Next move if its time has come
+    pub fn next_due(&mut self) -> Option<ReplayMove> {
+        match self.replay.moves.get(self.position) {
+            Some(m) if !self.paused && m.at as f64 <= self.clock => {
+                self.position += 1;
+                Some(*m)
+            }
+            _ => None
+        }
+    }
+
+    ///Jump to the board after `position` moves
+    pub fn seek(&mut self, position: usize) -> Result<Gameboard, ReplayError> {
+        let position = position.min(self.replay.moves.len());
+        let board = self.replay.board_at(position)?;
+        self.position = position;
+        self.clock = match position {
+            0 => 0.0,
+            p => self.replay.moves[p - 1].at as f64
+        };
+        self.verified = None;
+        Ok(board)
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.position >= self.replay.moves.len()
+    }
+
+    pub fn toggle_pause(&mut self) {
+        self.paused = !self.paused;
+    }
+
+    pub fn faster(&mut self) {
+        self.speed = (self.speed * 2.0).min(Self::MAX_SPEED);
+    }
+
+    pub fn slower(&mut self) {
+        self.speed = (self.speed / 2.0).max(Self::MIN_SPEED);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn solved_in_two() -> Replay {
+        let mut rows: Vec<u16> = (1..=16).collect();
+        rows.swap(15, 14);
+        rows.swap(14, 10);
+        let mut replay = Replay::new(Origin::Board(Gameboard::from_rows(4, &rows)));
+        replay.record(Direction::Bottom, 150);
+        replay.record(Direction::Right, 400);
+        replay
+    }
+
+    #[test]
+    fn format_test() {
+        let mut replay = Replay::new(Origin::Seed(42));
+        replay.record(Direction::Right, 0);
+        replay.record(Direction::Bottom, 310);
+        replay.record(Direction::Left, 705);
+        assert_eq!(replay.to_string(), "seed=42; moves=RDL; times=0,310,705");
+        assert_eq!("seed=42; moves=RDL; times=0,310,705".parse::<Replay>().unwrap(), replay);
+    }
+
+    #[test]
+    fn round_trip_cells_test() {
+        let replay = solved_in_two();
+        let text = replay.to_string();
+        assert!(text.starts_with("cells=1,2,3,4,5,6,7,8,9,10,0,12,13,14,11,15;"));
+        assert_eq!(text.parse::<Replay>().unwrap(), replay);
+    }
+
+    #[test]
+    fn goal_test() {
+        let mut replay = Replay::new(Origin::Seed(7));
+        replay.goal = GoalLayout::Spiral;
+        replay.record(Direction::Left, 0);
+        assert_eq!(replay.to_string(), "seed=7; goal=spiral; moves=L; times=0");
+        let parsed = replay.to_string().parse::<Replay>().unwrap();
+        assert_eq!(parsed, replay);
+        assert_eq!(parsed.initial(), Gameboard::generate(7, 4, GoalLayout::Spiral, Difficulty::Hard));
+    }
+
+    #[test]
+    fn topology_test() {
+        let mut replay = Replay::new(Origin::Board("1 2/3 0".parse().unwrap()));
+        replay.topology = Topology::Torus;
+        //Zero field goes right across the edge
+        replay.record(Direction::Right, 0);
+        assert_eq!(replay.to_string(), "cells=1,2,3,0; topology=torus; moves=R; times=0");
+        let parsed = replay.to_string().parse::<Replay>().unwrap();
+        assert_eq!(parsed, replay);
+        assert_eq!(parsed.board_at(1).unwrap().cells, vec![1, 2, 4, 3]);
+    }
+
+    #[test]
+    fn designer_test() {
+        let mut replay = Replay::new(Origin::Board("1 2 3/4 # 0/6 5 0".parse().unwrap()));
+        replay.record_blank(1, Direction::Left, 0);
+        replay.record(Direction::Bottom, 300);
+        assert_eq!(replay.to_string(), "cells=1,2,3,4,#,0,6,5,0; moves=2LD; times=0,300");
+        let parsed = replay.to_string().parse::<Replay>().unwrap();
+        assert_eq!(parsed, replay);
+        assert!(parsed.verify().is_ok());
+        assert!("cells=1,2,0,0; moves=3L".parse::<Replay>().unwrap().verify().is_err());
+        assert!("cells=1,2,0,0; moves=0L".parse::<Replay>().is_err());
+        assert!("cells=1,2,0,0; moves=L2".parse::<Replay>().is_err());
+    }
+
+    #[test]
+    fn size_test() {
+        let mut replay = Replay::new(Origin::Seed(3));
+        replay.size = 3;
+        replay.difficulty = Difficulty::Easy;
+        assert_eq!(replay.to_string(), "seed=3; size=3; difficulty=easy; moves=; times=");
+        let parsed = replay.to_string().parse::<Replay>().unwrap();
+        assert_eq!(parsed, replay);
+        assert_eq!(parsed.initial().size, 3);
+        assert!("seed=3; size=17".parse::<Replay>().is_err());
+    }
+
+    #[test]
+    fn parse_errors_test() {
+        assert!("moves=RD".parse::<Replay>().is_err());
+        assert!("seed=1; moves=RX".parse::<Replay>().is_err());
+        assert!("seed=1; moves=RD; times=1".parse::<Replay>().is_err());
+        assert!("cells=1,1,2; moves=".parse::<Replay>().is_err());
+    }
+
+    #[test]
+    fn save_in_test() {
+        let dir = std::env::temp_dir().join(format!("fifteen_replays_{}", std::process::id()));
+        let replay = solved_in_two();
+        let first = replay.save_in(&dir).unwrap();
+        let second = replay.save_in(&dir).unwrap();
+        assert_ne!(first, second);
+        assert_eq!(Replay::load(&first).unwrap(), replay);
+        assert_eq!(Replay::load(&second).unwrap(), replay);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn verify_test() {
+        let mut replay = solved_in_two();
+        assert!(replay.verify().unwrap().is_over());
+        replay.moves.pop();
+        assert!(matches!(replay.verify(), Err(ReplayError::NotSolved)));
+        replay.record(Direction::Bottom, 500);
+        assert!(matches!(replay.verify(), Err(ReplayError::IllegalMove(1))));
+    }
+
+    #[test]
+    fn player_test() {
+        let mut player = ReplayPlayer::new(solved_in_two());
+        player.tick(0.1);
+        assert_eq!(player.next_due(), None);
+        player.faster();
+        player.tick(0.1);
+        assert_eq!(player.next_due().map(|m| m.direction), Some(Direction::Bottom));
+        assert_eq!(player.next_due(), None);
+        player.toggle_pause();
+        player.tick(10.0);
+        assert_eq!(player.next_due(), None);
+        let board = player.seek(2).unwrap();
+        assert!(board.is_over());
+        assert!(player.is_finished());
+        assert_eq!(player.seek(0).unwrap(), player.replay.initial());
+    }
+}
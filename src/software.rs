@@ -0,0 +1,167 @@
+//! Drawing without window: `Graphics` backend filling triangles of an RGBA image in memory.
+//! Draw state (stencil, scissor, blend mode) is not used, colors are alpha blended.
+//! It is slow, but enough for screenshots and tests of the view.
+
+use std::path::Path;
+
+use graphics::glyph_cache::rusttype::GlyphCache;
+use graphics::{DrawState, Graphics, ImageSize};
+use graphics::types::Color;
+use image::{Rgba, RgbaImage};
+use texture::{CreateTexture, Format, TextureOp, TextureSettings, UpdateTexture};
+
+///Directory for shared pictures of solved boards
+pub const SCREENSHOT_DIR: &str = "screenshots";
+
+///Glyphs of TrueType font drawn into software textures
+pub type SoftwareGlyphs = GlyphCache<'static, (), SoftwareTexture>;
+
+///Load font for drawing text with `SoftwareGraphics`
+pub fn glyphs<P: AsRef<Path>>(font: P) -> std::io::Result<SoftwareGlyphs> {
+    GlyphCache::new(font, (), TextureSettings::new())
+}
+
+///Texture kept as image in memory
+#[derive(Debug, Clone)]
+pub struct SoftwareTexture {
+    pub image: RgbaImage,
+}
+
+impl ImageSize for SoftwareTexture {
+    fn get_size(&self) -> (u32, u32) {
+        self.image.dimensions()
+    }
+}
+
+impl TextureOp<()> for SoftwareTexture {
+    type Error = String;
+}
+
+impl CreateTexture<()> for SoftwareTexture {
+    fn create<S: Into<[u32; 2]>>(_: &mut (), _: Format, memory: &[u8], size: S, _: &TextureSettings) -> Result<Self, String> {
+        let [w, h] = size.into();
+        RgbaImage::from_raw(w, h, memory.to_vec())
+            .map(|image| SoftwareTexture { image })
+            .ok_or_else(|| format!("{} bytes are not {}x{} image", memory.len(), w, h))
+    }
+}
+
+impl UpdateTexture<()> for SoftwareTexture {
+    fn update<O, S>(&mut self, _: &mut (), _: Format, memory: &[u8], offset: O, size: S) -> Result<(), String>
+        where O: Into<[u32; 2]>, S: Into<[u32; 2]>
+    {
+        let ([x, y], [w, h]) = (offset.into(), size.into());
+        if memory.len() < (w * h * 4) as usize || x + w > self.image.width() || y + h > self.image.height() {
+            return Err(format!("{}x{} update at {},{} is out of texture", w, h, x, y));
+        }
+        for (i, pixel) in memory.chunks(4).take((w * h) as usize).enumerate() {
+            let (px, py) = (x + i as u32 % w, y + i as u32 / w);
+            self.image.put_pixel(px, py, Rgba([pixel[0], pixel[1], pixel[2], pixel[3]]));
+        }
+        Ok(())
+    }
+}
+
+///Graphics drawing into `image`
+pub struct SoftwareGraphics {
+    pub image: RgbaImage,
+}
+
+impl SoftwareGraphics {
+    pub fn new(width: u32, height: u32) -> Self {
+        SoftwareGraphics { image: RgbaImage::new(width, height) }
+    }
+
+    ///Fill triangles of `vertices` in view coordinates, `color_at` gives color by triangle and barycentric weights.
+    ///Pixels out of `scissor` rectangle are not changed
+    fn fill<F: FnMut(usize, [f32; 3]) -> Color>(&mut self, vertices: &[[f32; 2]], scissor: Option<[u32; 4]>, mut color_at: F) {
+        let (w, h) = (self.image.width() as f64, self.image.height() as f64);
+        let [sx, sy, sw, sh] = scissor.unwrap_or([0, 0, w as u32, h as u32]);
+        //View coordinates go from -1 to 1, y is up. Points are put on 1/256 pixel grid,
+        //so edge functions are exact and triangles with common edge have no gaps
+        let snap = |x: f64| (x * 256.0).round() / 256.0;
+        let pixel = |v: [f32; 2]| [snap((v[0] as f64 + 1.0) * 0.5 * w), snap((1.0 - v[1] as f64) * 0.5 * h)];
+        for (t, triangle) in vertices.chunks_exact(3).enumerate() {
+            let [a, b, c] = [pixel(triangle[0]), pixel(triangle[1]), pixel(triangle[2])];
+            let area = edge(a, b, c);
+            if area == 0.0 {
+                continue;
+            }
+            let edges = [(b, c), (c, a), (a, b)];
+            let x0 = (a[0].min(b[0]).min(c[0]).floor().max(0.0) as u32).max(sx);
+            let y0 = (a[1].min(b[1]).min(c[1]).floor().max(0.0) as u32).max(sy);
+            let x1 = (a[0].max(b[0]).max(c[0]).ceil().clamp(0.0, w) as u32).min(sx + sw);
+            let y1 = (a[1].max(b[1]).max(c[1]).ceil().clamp(0.0, h) as u32).min(sy + sh);
+            for y in y0..y1 {
+                for x in x0..x1 {
+                    let p = [x as f64 + 0.5, y as f64 + 0.5];
+                    let weights = edges.map(|(s, e)| edge(s, e, p) / area);
+                    //Center on common edge belongs to one triangle only
+                    let inside = weights.iter().zip(edges.iter())
+                        .all(|(weight, (s, e))| *weight > 0.0 || (*weight == 0.0 && is_top_left(*s, *e, area)));
+                    if inside {
+                        let color = color_at(t, weights.map(|w| w as f32));
+                        blend(self.image.get_pixel_mut(x, y), color);
+                    }
+                }
+            }
+        }
+    }
+}
+
+///Twice the signed area of triangle `a`, `b`, `p`
+fn edge(a: [f64; 2], b: [f64; 2], p: [f64; 2]) -> f64 {
+    (b[0] - a[0]) * (p[1] - a[1]) - (b[1] - a[1]) * (p[0] - a[0])
+}
+
+///Edge from `a` to `b` of triangle with `area` is top or left one
+fn is_top_left(a: [f64; 2], b: [f64; 2], area: f64) -> bool {
+    let (dx, dy) = if area > 0.0 { (b[0] - a[0], b[1] - a[1]) } else { (a[0] - b[0], a[1] - b[1]) };
+    (dy == 0.0 && dx < 0.0) || dy > 0.0
+}
+
+///Put `color` over the pixel by its alpha
+fn blend(pixel: &mut Rgba<u8>, color: Color) {
+    let alpha = color[3].clamp(0.0, 1.0);
+    for i in 0..3 {
+        let old = pixel[i] as f32 / 255.0;
+        pixel[i] = ((color[i].clamp(0.0, 1.0) * alpha + old * (1.0 - alpha)) * 255.0).round() as u8;
+    }
+    let old = pixel[3] as f32 / 255.0;
+    pixel[3] = ((alpha + old * (1.0 - alpha)) * 255.0).round() as u8;
+}
+
+impl Graphics for SoftwareGraphics {
+    type Texture = SoftwareTexture;
+
+    fn clear_color(&mut self, color: Color) {
+        let rgba = Rgba([0, 1, 2, 3].map(|i| (color[i].clamp(0.0, 1.0) * 255.0).round() as u8));
+        for pixel in self.image.pixels_mut() {
+            *pixel = rgba;
+        }
+    }
+
+    fn clear_stencil(&mut self, _value: u8) {}
+
+    fn tri_list<F>(&mut self, draw_state: &DrawState, color: &[f32; 4], mut f: F)
+        where F: FnMut(&mut dyn FnMut(&[[f32; 2]]))
+    {
+        let color = *color;
+        f(&mut |vertices| self.fill(vertices, draw_state.scissor, |_, _| color));
+    }
+
+    fn tri_list_uv<F>(&mut self, draw_state: &DrawState, color: &[f32; 4], texture: &SoftwareTexture, mut f: F)
+        where F: FnMut(&mut dyn FnMut(&[[f32; 2]], &[[f32; 2]]))
+    {
+        let (tw, th) = texture.image.dimensions();
+        f(&mut |vertices, uvs| {
+            self.fill(vertices, draw_state.scissor, |t, weights| {
+                let corners = &uvs[t * 3..t * 3 + 3];
+                let u: f32 = (0..3).map(|i| weights[i] * corners[i][0]).sum();
+                let v: f32 = (0..3).map(|i| weights[i] * corners[i][1]).sum();
+                let texel = texture.image.get_pixel(((u * tw as f32) as u32).min(tw - 1), ((v * th as f32) as u32).min(th - 1));
+                [0, 1, 2, 3].map(|i| color[i] * texel[i] as f32 / 255.0)
+            });
+        });
+    }
+}
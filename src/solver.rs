@@ -0,0 +1,334 @@
+//! Optimal solver: IDA* with Manhattan distance and linear conflicts.
+//! Both are counted to the board goal layout, so any `GoalLayout` can be solved.
+//! On torus the distance goes the shorter way around and tiles in conflict can go around each other,
+//! so only Manhattan distance is counted.
+
+use std::error::Error;
+use std::fmt;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::animator::Direction;
+use crate::gameboard::{Gameboard, Position, MAX_SIZE};
+use crate::topology::Topology;
+
+///Shortest solution found by `solve`
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Solution {
+    ///Moves of the zero field
+    pub moves: Vec<Direction>,
+    ///Count of expanded nodes
+    pub nodes: u64,
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum SolveError {
+    ///Board can't be put in order of its goal
+    Unsolvable,
+    ///Search was stopped after this count of nodes
+    NodeLimit(u64),
+    ///Board has obstacles or several zero fields
+    Unsupported,
+    ///Search was stopped by its cancel flag
+    Canceled,
+}
+
+impl fmt::Display for SolveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SolveError::Unsolvable => write!(f, "board can't be solved"),
+            SolveError::NodeLimit(n) => write!(f, "no solution found in {} nodes", n),
+            SolveError::Unsupported => write!(f, "boards with obstacles or several zero fields aren't solved"),
+            SolveError::Canceled => write!(f, "search was canceled"),
+        }
+    }
+}
+
+impl Error for SolveError {}
+
+///Optimal solution of `board`
+pub fn solve(board: &Gameboard) -> Result<Solution, SolveError> {
+    solve_limited(board, None)
+}
+
+///Optimal solution of `board`, search gives up after `max_nodes` expanded nodes
+pub fn solve_limited(board: &Gameboard, max_nodes: Option<u64>) -> Result<Solution, SolveError> {
+    solve_cancelable(board, max_nodes, &AtomicBool::new(false))
+}
+
+///Optimal solution of `board` in background thread, search stops when `cancel` is set
+pub fn solve_cancelable(board: &Gameboard, max_nodes: Option<u64>, cancel: &AtomicBool) -> Result<Solution, SolveError> {
+    if !board.is_classic() {
+        return Err(SolveError::Unsupported);
+    }
+    if !board.is_solvable() {
+        return Err(SolveError::Unsolvable);
+    }
+    let mut search = Search::new(board, max_nodes);
+    search.cancel = Some(cancel);
+    let mut bound = search.heuristic();
+    loop {
+        match search.search(0, bound) {
+            Step::Found => return Ok(Solution { moves: search.path, nodes: search.nodes }),
+            Step::Limit => return Err(SolveError::NodeLimit(search.nodes)),
+            Step::Canceled => return Err(SolveError::Canceled),
+            Step::Exceeded(next) => bound = next,
+        }
+    }
+}
+
+///Lower bound of solution length: Manhattan distance plus two moves for every linear conflict
+pub fn heuristic(board: &Gameboard) -> usize {
+    Search::new(board, None).heuristic()
+}
+
+enum Step {
+    Found,
+    Limit,
+    Canceled,
+    ///Smallest estimate over the bound
+    Exceeded(usize),
+}
+
+struct Search<'a> {
+    size: usize,
+    topology: Topology,
+    cells: Vec<u16>,
+    blank: u16,
+    zero: usize,
+    goal_row: Vec<usize>,
+    goal_col: Vec<usize>,
+    manhattan: usize,
+    ///Tiles to take out of every row and column to put the rest in goal order
+    row_conflicts: Vec<usize>,
+    col_conflicts: Vec<usize>,
+    path: Vec<Direction>,
+    nodes: u64,
+    max_nodes: Option<u64>,
+    cancel: Option<&'a AtomicBool>,
+}
+
+impl<'a> Search<'a> {
+    fn new(board: &Gameboard, max_nodes: Option<u64>) -> Self {
+        let size = board.size;
+        let mut goal_row = vec![0; size * size + 1];
+        let mut goal_col = vec![0; size * size + 1];
+        for (i, v) in board.goal.cells(size).iter().enumerate() {
+            goal_row[*v as usize] = i / size;
+            goal_col[*v as usize] = i % size;
+        }
+        let mut search = Search {
+            size,
+            topology: board.topology,
+            cells: board.cells.clone(),
+            blank: board.blank(),
+            zero: board.zero().index(size),
+            goal_row,
+            goal_col,
+            manhattan: 0,
+            row_conflicts: vec![0; size],
+            col_conflicts: vec![0; size],
+            path: vec![],
+            nodes: 0,
+            max_nodes,
+            cancel: None,
+        };
+        search.manhattan = (0..size * size).map(|i| search.distance(i)).sum();
+        for line in 0..size {
+            search.row_conflicts[line] = search.conflicts(line, true);
+            search.col_conflicts[line] = search.conflicts(line, false);
+        }
+        search
+    }
+
+    fn heuristic(&self) -> usize {
+        self.manhattan + 2 * (self.row_conflicts.iter().sum::<usize>() + self.col_conflicts.iter().sum::<usize>())
+    }
+
+    ///Manhattan distance of the tile at `index` to its goal place
+    fn distance(&self, index: usize) -> usize {
+        let v = self.cells[index] as usize;
+        if self.cells[index] == self.blank {
+            return 0;
+        }
+        let (row, col) = (index / self.size, index % self.size);
+        self.topology.distance(row, self.goal_row[v], self.size) + self.topology.distance(col, self.goal_col[v], self.size)
+    }
+
+    ///Tiles of the row (or column) with goal in it, which must leave it to let others pass.
+    ///It is the length of the line minus the longest increasing run of goal places
+    fn conflicts(&self, line: usize, row: bool) -> usize {
+        if self.topology == Topology::Torus {
+            return 0;
+        }
+        let mut places = [0; MAX_SIZE];
+        let mut count = 0;
+        for i in 0..self.size {
+            let index = if row { line * self.size + i } else { i * self.size + line };
+            let v = self.cells[index];
+            if v == self.blank {
+                continue;
+            }
+            let v = v as usize;
+            if row && self.goal_row[v] == line {
+                places[count] = self.goal_col[v];
+                count += 1;
+            } else if !row && self.goal_col[v] == line {
+                places[count] = self.goal_row[v];
+                count += 1;
+            }
+        }
+        let mut longest = [1; MAX_SIZE];
+        for i in 0..count {
+            for j in 0..i {
+                if places[j] < places[i] {
+                    longest[i] = longest[i].max(longest[j] + 1);
+                }
+            }
+        }
+        count - longest[..count].iter().max().copied().unwrap_or(0)
+    }
+
+    ///Index of the field next to zero in `direction`
+    fn neighbour(&self, direction: Direction) -> Option<usize> {
+        self.topology.step(Position::from_index(self.zero, self.size), direction, self.size)
+            .map(|cell| cell.index(self.size))
+    }
+
+    ///Slide tile at `index` into zero field and update the estimate
+    fn slide(&mut self, index: usize) {
+        let zero = self.zero;
+        self.manhattan -= self.distance(index);
+        self.cells.swap(zero, index);
+        self.zero = index;
+        self.manhattan += self.distance(zero);
+        //Order of tiles changes only in lines the tile goes across
+        if zero / self.size == index / self.size {
+            for col in [zero % self.size, index % self.size] {
+                self.col_conflicts[col] = self.conflicts(col, false);
+            }
+        } else {
+            for row in [zero / self.size, index / self.size] {
+                self.row_conflicts[row] = self.conflicts(row, true);
+            }
+        }
+    }
+
+    fn search(&mut self, cost: usize, bound: usize) -> Step {
+        let estimate = self.heuristic();
+        if cost + estimate > bound {
+            return Step::Exceeded(cost + estimate);
+        }
+        if estimate == 0 {
+            return Step::Found;
+        }
+        self.nodes += 1;
+        if self.max_nodes.is_some_and(|max| self.nodes > max) {
+            return Step::Limit;
+        }
+        if self.cancel.is_some_and(|cancel| cancel.load(Ordering::Relaxed)) {
+            return Step::Canceled;
+        }
+        let mut next = usize::MAX;
+        for direction in Direction::ALL {
+            //Step back is never in the shortest path
+            if self.path.last() == Some(&direction.opposite()) {
+                continue;
+            }
+            let index = match self.neighbour(direction) {
+                Some(index) => index,
+                None => continue
+            };
+            let zero = self.zero;
+            self.slide(index);
+            self.path.push(direction);
+            match self.search(cost + 1, bound) {
+                Step::Found => return Step::Found,
+                Step::Limit => return Step::Limit,
+                Step::Canceled => return Step::Canceled,
+                Step::Exceeded(estimate) => next = next.min(estimate),
+            }
+            self.path.pop();
+            self.slide(zero);
+        }
+        Step::Exceeded(next)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::{HashMap, VecDeque};
+    use crate::difficulty::Difficulty;
+    use crate::goal::GoalLayout;
+
+    ///Distances of all 3x3 boards from the goal by breadth-first search
+    fn distances(goal: GoalLayout, topology: Topology) -> HashMap<Vec<u16>, usize> {
+        let solved = Gameboard { topology, ..Gameboard::solved(3, goal) };
+        let mut distances = HashMap::new();
+        distances.insert(solved.cells.clone(), 0);
+        let mut queue = VecDeque::from(vec![solved]);
+        while let Some(board) = queue.pop_front() {
+            let distance = distances[&board.cells];
+            for direction in Direction::ALL {
+                let mut next = board.clone();
+                if next.move_zero(direction) && !distances.contains_key(&next.cells) {
+                    distances.insert(next.cells.clone(), distance + 1);
+                    queue.push_back(next);
+                }
+            }
+        }
+        distances
+    }
+
+    #[test]
+    fn optimal_test() {
+        for goal in &[GoalLayout::Standard, GoalLayout::Spiral] {
+            let distances = distances(*goal, Topology::Flat);
+            assert_eq!(distances.len(), 181440);
+            for seed in 0..30 {
+                let board = Gameboard::generate(seed, 3, *goal, Difficulty::Hard);
+                let solution = solve(&board).unwrap();
+                assert_eq!(solution.moves.len(), distances[&board.cells]);
+                assert!(heuristic(&board) <= solution.moves.len());
+                let mut board = board;
+                for direction in &solution.moves {
+                    assert!(board.move_zero(*direction));
+                }
+                assert!(board.is_over());
+            }
+        }
+    }
+
+    #[test]
+    fn solve_4x4_test() {
+        let board: Gameboard = "1 2 3 4/5 6 7 8/9 10 11 12/13 14 15 0".parse().unwrap();
+        assert_eq!(solve(&board).unwrap(), Solution { moves: vec![], nodes: 0 });
+        //Easy board is 16 random moves from the goal
+        let board = Gameboard::generate(3, 4, GoalLayout::Standard, Difficulty::Easy);
+        assert!(solve(&board).unwrap().moves.len() <= 16);
+        let unsolvable: Gameboard = "1 2 3 4/5 6 7 8/9 10 11 12/13 15 14 0".parse().unwrap();
+        assert_eq!(solve(&unsolvable), Err(SolveError::Unsolvable));
+        assert_eq!(solve(&"1 2 # 0".parse().unwrap()), Err(SolveError::Unsupported));
+        let far: Gameboard = "0 12 9 13/15 11 10 14/3 7 2 5/4 8 6 1".parse().unwrap();
+        assert_eq!(solve_limited(&far, Some(100)), Err(SolveError::NodeLimit(101)));
+        assert_eq!(solve_cancelable(&far, None, &AtomicBool::new(true)), Err(SolveError::Canceled));
+    }
+
+    #[test]
+    fn torus_test() {
+        //Every board of odd torus can be solved
+        let distances = distances(GoalLayout::Standard, Topology::Torus);
+        assert_eq!(distances.len(), 362880);
+        for seed in 0..10 {
+            let mut board = Gameboard::generate(seed, 3, GoalLayout::Standard, Difficulty::Hard);
+            board.topology = Topology::Torus;
+            board.cells.swap(0, 1);
+            let solution = solve(&board).unwrap();
+            assert_eq!(solution.moves.len(), distances[&board.cells]);
+            assert!(heuristic(&board) <= solution.moves.len());
+        }
+        let mut board: Gameboard = "8 1 2/3 4 5/6 7 0".parse().unwrap();
+        board.topology = Topology::Torus;
+        assert!(solve(&board).unwrap().moves.len() < 20);
+    }
+}
@@ -0,0 +1,286 @@
+//! Statistics of finished games, stored as JSON Lines in user data directory.
+//! Games of `stats.json` written by older versions are moved into the new file once.
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+use crate::jsonl;
+
+/// Seconds in a day, streaks are counted by UTC days
+const DAY: u64 = 24 * 60 * 60;
+
+///One finished game
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GameRecord {
+    ///Width of the board
+    pub size: usize,
+    pub seed: Option<u64>,
+    pub difficulty: String,
+    pub moves: usize,
+    ///Seconds from start to win
+    pub time: f64,
+    pub hints: usize,
+    ///Length of optimal solution, when solver knows it
+    pub optimal: Option<usize>,
+    ///Unix time of the win
+    pub finished_at: u64,
+    ///Day of the daily challenge, if the game was one
+    #[serde(default)]
+    pub daily: Option<u64>,
+    ///Rating of moves against optimal length or par, see `stars`
+    #[serde(default)]
+    pub stars: Option<u8>,
+}
+
+///Best results for one size and difficulty, may be from different games
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct PersonalBest {
+    pub moves: usize,
+    pub time: f64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Summary {
+    pub games: usize,
+    pub average_moves: f64,
+    pub average_time: f64,
+    ///Count of games by moves, bucket `i` is `i * HISTOGRAM_BUCKET..(i + 1) * HISTOGRAM_BUCKET`
+    pub histogram: Vec<usize>,
+    ///Days in a row with a win, up to today or yesterday
+    pub current_streak: usize,
+    pub best_streak: usize,
+}
+
+///File of older versions with all games in one JSON object
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct StatsFile {
+    games: Vec<GameRecord>,
+}
+
+pub struct StatsStore {
+    path: PathBuf,
+    pub records: Vec<GameRecord>,
+}
+
+impl StatsStore {
+    ///Width of histogram bucket in moves
+    pub const HISTOGRAM_BUCKET: usize = 50;
+
+    ///`stats.jsonl` in user data directory
+    pub fn default_path() -> Option<PathBuf> {
+        dirs::data_dir().map(|dir| dir.join("fifteen").join("stats.jsonl"))
+    }
+
+    ///Read stats from `path`, no file means no games yet
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let old = path.with_extension("json");
+        if !path.exists() && old != path && old.exists() {
+            let file: StatsFile = serde_json::from_str(&fs::read_to_string(&old)?)
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+            for record in &file.games {
+                jsonl::append(&path, record)?;
+            }
+            fs::remove_file(&old)?;
+        }
+        Ok(StatsStore { records: jsonl::read(&path)?, path })
+    }
+
+    ///Append the game to the file, games added there by another instance of the game are read too
+    pub fn add(&mut self, record: GameRecord) -> io::Result<()> {
+        jsonl::append(&self.path, &record)?;
+        self.records = jsonl::read(&self.path)?;
+        Ok(())
+    }
+
+    pub fn best(&self, size: usize, difficulty: &str) -> Option<PersonalBest> {
+        let games: Vec<&GameRecord> = self.records.iter()
+            .filter(|r| r.size == size && r.difficulty == difficulty)
+            .collect();
+        let moves = games.iter().map(|r| r.moves).min()?;
+        let time = games.iter().map(|r| r.time).fold(f64::INFINITY, f64::min);
+        Some(PersonalBest { moves, time })
+    }
+
+    ///Summary of all games, `today` is count of days from unix epoch
+    pub fn summary(&self, today: u64) -> Summary {
+        let games = self.records.len();
+        let average = |f: &dyn Fn(&GameRecord) -> f64| match games {
+            0 => 0.0,
+            n => self.records.iter().map(f).sum::<f64>() / n as f64
+        };
+        let mut histogram = vec![];
+        for r in &self.records {
+            let bucket = r.moves / Self::HISTOGRAM_BUCKET;
+            if histogram.len() <= bucket {
+                histogram.resize(bucket + 1, 0);
+            }
+            histogram[bucket] += 1;
+        }
+
+        let (current_streak, best_streak) = streaks(self.records.iter().map(|r| r.finished_at / DAY).collect(), today);
+
+        Summary {
+            games,
+            average_moves: average(&|r| r.moves as f64),
+            average_time: average(&|r| r.time),
+            histogram,
+            current_streak,
+            best_streak,
+        }
+    }
+
+    ///Daily challenge of `day` is solved
+    pub fn daily_completed(&self, day: u64) -> bool {
+        self.records.iter().any(|r| r.daily == Some(day))
+    }
+
+    ///Current and best count of daily challenges solved in a row
+    pub fn daily_streak(&self, today: u64) -> (usize, usize) {
+        streaks(self.records.iter().filter_map(|r| r.daily).collect(), today)
+    }
+}
+
+///Current and best count of days in a row. Current streak goes up to today or yesterday
+fn streaks(mut days: Vec<u64>, today: u64) -> (usize, usize) {
+    days.sort_unstable();
+    days.dedup();
+    let mut best_streak = 0;
+    let mut streak = 0;
+    for (i, day) in days.iter().enumerate() {
+        streak = if i > 0 && days[i - 1] + 1 == *day { streak + 1 } else { 1 };
+        best_streak = best_streak.max(streak);
+    }
+    let current_streak = match days.last() {
+        Some(last) if *last + 1 >= today => streak,
+        _ => 0
+    };
+    (current_streak, best_streak)
+}
+
+///Most stars for one game
+pub const MAX_STARS: u8 = 3;
+
+///3 stars for `moves` not more than `reference` (optimal length or par), 2 for +20%, 1 for +50%
+pub fn stars(moves: usize, reference: usize) -> u8 {
+    if moves <= reference {
+        3
+    } else if moves * 10 <= reference * 12 {
+        2
+    } else if moves * 10 <= reference * 15 {
+        1
+    } else {
+        0
+    }
+}
+
+///Seconds from unix epoch
+pub fn now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0)
+}
+
+///Days from unix epoch
+pub fn today() -> u64 {
+    now() / DAY
+}
+
+///Seconds as `m:ss`
+pub fn format_time(seconds: f64) -> String {
+    let seconds = seconds as u64;
+    format!("{}:{:02}", seconds / 60, seconds % 60)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(moves: usize, time: f64, day: u64) -> GameRecord {
+        GameRecord {
+            size: 4, seed: Some(1), difficulty: "random".to_owned(), moves, time,
+            hints: 0, optimal: None, finished_at: day * DAY + 100, daily: None, stars: None,
+        }
+    }
+
+    #[test]
+    fn save_load_test() {
+        let path = std::env::temp_dir().join(format!("fifteen_stats_{}.jsonl", std::process::id()));
+        let _ = fs::remove_file(&path);
+        let mut store = StatsStore::load(&path).unwrap();
+        assert!(store.records.is_empty());
+        store.add(record(80, 60.5, 10)).unwrap();
+        let loaded = StatsStore::load(&path).unwrap();
+        assert_eq!(loaded.records, store.records);
+        //Two windows of the game write the same file
+        let mut other = StatsStore::load(&path).unwrap();
+        other.add(record(70, 20.0, 11)).unwrap();
+        store.add(record(90, 40.0, 12)).unwrap();
+        let loaded = StatsStore::load(&path).unwrap();
+        assert_eq!(loaded.records.iter().map(|r| r.moves).collect::<Vec<_>>(), vec![80, 70, 90]);
+        assert_eq!(store.records, loaded.records);
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn old_file_test() {
+        let path = std::env::temp_dir().join(format!("fifteen_old_stats_{}.jsonl", std::process::id()));
+        let old = path.with_extension("json");
+        let _ = fs::remove_file(&path);
+        let file = StatsFile { games: vec![record(80, 60.5, 10), record(70, 20.0, 11)] };
+        fs::write(&old, serde_json::to_string_pretty(&file).unwrap()).unwrap();
+        let mut store = StatsStore::load(&path).unwrap();
+        assert_eq!(store.records, file.games);
+        assert!(!old.exists());
+        store.add(record(90, 40.0, 12)).unwrap();
+        assert_eq!(StatsStore::load(&path).unwrap().records.len(), 3);
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn best_test() {
+        let mut store = StatsStore { path: PathBuf::new(), records: vec![record(80, 30.0, 1), record(60, 90.0, 2)] };
+        store.records[0].difficulty = "easy".to_owned();
+        assert_eq!(store.best(4, "random"), Some(PersonalBest { moves: 60, time: 90.0 }));
+        store.records[0].difficulty = "random".to_owned();
+        assert_eq!(store.best(4, "random"), Some(PersonalBest { moves: 60, time: 30.0 }));
+        assert_eq!(store.best(5, "random"), None);
+    }
+
+    #[test]
+    fn summary_test() {
+        let store = StatsStore {
+            path: PathBuf::new(),
+            records: vec![record(40, 10.0, 1), record(120, 30.0, 2), record(60, 20.0, 3), record(70, 20.0, 3), record(10, 20.0, 9)],
+        };
+        let summary = store.summary(10);
+        assert_eq!(summary.games, 5);
+        assert_eq!(summary.average_moves, 60.0);
+        assert_eq!(summary.average_time, 20.0);
+        assert_eq!(summary.histogram, vec![2, 2, 1]);
+        assert_eq!(summary.best_streak, 3);
+        assert_eq!(summary.current_streak, 1);
+        assert_eq!(store.summary(11).current_streak, 0);
+        assert_eq!(format_time(75.9), "1:15");
+        assert_eq!([20, 24, 25, 30, 31].iter().map(|m| stars(*m, 20)).collect::<Vec<u8>>(), vec![3, 2, 1, 1, 0]);
+    }
+
+    #[test]
+    fn daily_test() {
+        let mut store = StatsStore { path: PathBuf::new(), records: vec![] };
+        for (day, daily) in &[(3, Some(3)), (4, None), (5, Some(4)), (5, Some(5)), (7, Some(7))] {
+            let mut r = record(50, 10.0, *day);
+            r.daily = *daily;
+            store.records.push(r);
+        }
+        assert!(store.daily_completed(4));
+        assert!(!store.daily_completed(6));
+        assert_eq!(store.daily_streak(8), (1, 3));
+        assert_eq!(store.daily_streak(9), (0, 3));
+        //Old files don't have daily field
+        let old: GameRecord = serde_json::from_str(r#"{"size":4,"seed":null,"difficulty":"hard","moves":1,"time":1.0,"hints":0,"optimal":null,"finished_at":5}"#).unwrap();
+        assert_eq!(old.daily, None);
+    }
+}
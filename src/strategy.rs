@@ -0,0 +1,375 @@
+//! Human method solver: the first row, then the first column, then the rest the same way
+//! until the last 2x2 square. Solutions are long, but it is fast for any board size,
+//! and every move knows the sub-goal it serves, so hints can tell why.
+//!
+//! Every sub-goal is a small breadth-first search of the zero field and one or two tiles,
+//! solved tiles are not moved again. The last two tiles of a line go in together:
+//! they are brought near their places first and then searched in a 3x3 window.
+//! Goal layouts with zero field in a corner are solved, the board is mirrored so it is the bottom-right one.
+//! Moves go inside the board, on odd torus one move across the edge changes parity first if it is needed.
+
+use std::collections::VecDeque;
+use std::error::Error;
+use std::fmt;
+
+use crate::animator::Direction;
+use crate::gameboard::Gameboard;
+use crate::topology::Topology;
+
+///Row or column of the board, counted from 0
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Line {
+    Row(usize),
+    Column(usize),
+}
+
+impl fmt::Display for Line {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Line::Row(row) => write!(f, "row {}", row + 1),
+            Line::Column(col) => write!(f, "column {}", col + 1),
+        }
+    }
+}
+
+///What a move is done for
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum SubGoal {
+    ///Tile to its place in the line
+    Place { tile: u16, line: Line },
+    ///Last two tiles of the line go in together
+    Pair { tiles: [u16; 2], line: Line },
+    ///Last 2x2 square is turned around
+    Square,
+    ///Zero field goes across the edge of torus once, so the rest can be solved inside the board
+    Across,
+}
+
+impl fmt::Display for SubGoal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SubGoal::Place { tile, line } => write!(f, "{}: place {}", line, tile),
+            SubGoal::Pair { tiles: [a, b], line } => write!(f, "{}: {} and {} together", line, a, b),
+            SubGoal::Square => write!(f, "last square"),
+            SubGoal::Across => write!(f, "across the edge once"),
+        }
+    }
+}
+
+///Move of the zero field and its sub-goal
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct PlannedMove {
+    pub direction: Direction,
+    pub goal: SubGoal,
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum StrategyError {
+    ///Board can't be put in order of its goal
+    Unsolvable,
+    ///Zero field of the goal layout isn't in a corner
+    Layout,
+    ///Board has obstacles or several zero fields
+    Unsupported,
+}
+
+impl fmt::Display for StrategyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StrategyError::Unsolvable => write!(f, "board can't be solved"),
+            StrategyError::Layout => write!(f, "zero field of the goal isn't in a corner"),
+            StrategyError::Unsupported => write!(f, "board has obstacles or several zero fields"),
+        }
+    }
+}
+
+impl Error for StrategyError {}
+
+///Moves of the human method which solve `board`
+pub fn solve(board: &Gameboard) -> Result<Vec<PlannedMove>, StrategyError> {
+    if !board.is_classic() {
+        return Err(StrategyError::Unsupported);
+    }
+    let n = board.size;
+    let goal = board.goal.cells(n);
+    let blank_goal = goal.iter().position(|v| *v == board.blank()).unwrap_or(0);
+    let (row, col) = (blank_goal / n, blank_goal % n);
+    if (row != 0 && row != n - 1) || (col != 0 && col != n - 1) {
+        return Err(StrategyError::Layout);
+    }
+    if !board.is_solvable() {
+        return Err(StrategyError::Unsolvable);
+    }
+    let mut board = board.clone();
+    let mut across = vec![];
+    let flat = Gameboard { topology: Topology::Flat, ..board.clone() };
+    if !flat.is_solvable() {
+        //Only odd torus gets here, zero field goes to the right edge and across it
+        while across.last() != Some(&Direction::Right) || board.zero().col != 0 {
+            board.move_zero(Direction::Right);
+            across.push(Direction::Right);
+        }
+    }
+    //Mirrored so zero field of the goal is the bottom-right corner
+    let (flip_rows, flip_cols) = (row == 0 && n > 1, col == 0 && n > 1);
+    let mirror = |i: usize| {
+        let (r, c) = (i / n, i % n);
+        let r = if flip_rows { n - 1 - r } else { r };
+        let c = if flip_cols { n - 1 - c } else { c };
+        r * n + c
+    };
+    let mut cells = vec![0; n * n];
+    let mut targets = vec![0; n * n];
+    for i in 0..n * n {
+        cells[mirror(i)] = board.cells[i];
+        targets[mirror(i)] = goal[i];
+    }
+    let moves = across.into_iter().map(|direction| PlannedMove { direction, goal: SubGoal::Across }).collect();
+    let mut plan = Plan { n, cells, targets, fixed: vec![false; n * n], moves, flip_rows, flip_cols };
+    for k in 0..n.saturating_sub(2) {
+        for c in k..n - 2 {
+            plan.place(k * n + c, plan.row(k))?;
+        }
+        plan.pair(k * n + n - 2, k * n + n - 1, window(n, k, n - 3), plan.row(k))?;
+        for r in k + 1..n - 2 {
+            plan.place(r * n + k, plan.column(k))?;
+        }
+        plan.pair((n - 2) * n + k, (n - 1) * n + k, window(n, n - 3, k), plan.column(k))?;
+    }
+    plan.square()?;
+    Ok(plan.moves)
+}
+
+///3x3 cells from `row`, `col`
+fn window(n: usize, row: usize, col: usize) -> Vec<usize> {
+    (row..row + 3).flat_map(|r| (col..col + 3).map(move |c| r * n + c)).collect()
+}
+
+///Mirrored board being solved
+struct Plan {
+    n: usize,
+    cells: Vec<u16>,
+    ///Tile of every cell on solved board
+    targets: Vec<u16>,
+    ///Solved cells which are not moved any more
+    fixed: Vec<bool>,
+    moves: Vec<PlannedMove>,
+    flip_rows: bool,
+    flip_cols: bool,
+}
+
+impl Plan {
+    fn row(&self, k: usize) -> Line {
+        Line::Row(if self.flip_rows { self.n - 1 - k } else { k })
+    }
+
+    fn column(&self, k: usize) -> Line {
+        Line::Column(if self.flip_cols { self.n - 1 - k } else { k })
+    }
+
+    fn cell_of(&self, tile: u16) -> usize {
+        self.cells.iter().position(|v| *v == tile).unwrap_or(0)
+    }
+
+    ///Cells which may be used, without fixed ones and `blocked`
+    fn free(&self, blocked: &[usize]) -> Vec<bool> {
+        (0..self.cells.len()).map(|i| !self.fixed[i] && !blocked.contains(&i)).collect()
+    }
+
+    ///Tile of `cell` to its place
+    fn place(&mut self, cell: usize, line: Line) -> Result<(), StrategyError> {
+        let tile = self.targets[cell];
+        let goal = SubGoal::Place { tile, line };
+        let moves = search(self.n, &self.cells, &self.free(&[]), &[tile], |_, at| at[0] == cell)
+            .ok_or(StrategyError::Unsolvable)?;
+        self.apply(&moves, goal);
+        self.fixed[cell] = true;
+        Ok(())
+    }
+
+    ///Two last tiles of a line to cells `a` and `b`, searched near them in `window`
+    fn pair(&mut self, a: usize, b: usize, window: Vec<usize>, line: Line) -> Result<(), StrategyError> {
+        let tiles = [self.targets[a], self.targets[b]];
+        let goal = SubGoal::Pair { tiles, line };
+        let done = |_: usize, at: &[usize]| at[0] == a && at[1] == b;
+        if done(0, &[self.cell_of(tiles[0]), self.cell_of(tiles[1])]) {
+            self.fixed[a] = true;
+            self.fixed[b] = true;
+            return Ok(());
+        }
+        //Both tiles and zero field come to the window, other tiles don't matter
+        let near = search(self.n, &self.cells, &self.free(&[]), &tiles[..1], |_, at| window.contains(&at[0]));
+        if let Some(moves) = near {
+            self.apply(&moves, goal);
+        }
+        let near = search(self.n, &self.cells, &self.free(&[self.cell_of(tiles[0])]), &tiles[1..], |_, at| window.contains(&at[0]));
+        if let Some(moves) = near {
+            self.apply(&moves, goal);
+        }
+        let blocked = [self.cell_of(tiles[0]), self.cell_of(tiles[1])];
+        if let Some(moves) = search(self.n, &self.cells, &self.free(&blocked), &[], |zero, _| window.contains(&zero)) {
+            self.apply(&moves, goal);
+        }
+        let in_window: Vec<bool> = self.free(&[]).iter().enumerate().map(|(i, free)| *free && window.contains(&i)).collect();
+        let moves = search(self.n, &self.cells, &in_window, &tiles, done)
+            .or_else(|| search(self.n, &self.cells, &self.free(&[]), &tiles, done))
+            .ok_or(StrategyError::Unsolvable)?;
+        self.apply(&moves, goal);
+        self.fixed[a] = true;
+        self.fixed[b] = true;
+        Ok(())
+    }
+
+    ///Last 2x2 square, its three tiles are put in order
+    fn square(&mut self) -> Result<(), StrategyError> {
+        let n = self.n;
+        let cells = [(n - 2) * n + n - 2, (n - 2) * n + n - 1, (n - 1) * n + n - 2];
+        let tiles = cells.map(|cell| self.targets[cell]);
+        let moves = search(n, &self.cells, &self.free(&[]), &tiles, |_, at| at == cells)
+            .ok_or(StrategyError::Unsolvable)?;
+        self.apply(&moves, SubGoal::Square);
+        Ok(())
+    }
+
+    ///Do mirrored `moves` and keep them as moves of the real board
+    fn apply(&mut self, moves: &[Direction], goal: SubGoal) {
+        for direction in moves {
+            let zero = self.cells.iter().position(|v| *v as usize == self.n * self.n).unwrap_or(0);
+            if let Some(next) = step(self.n, zero, *direction) {
+                self.cells.swap(zero, next);
+            }
+            let direction = match direction {
+                Direction::Top | Direction::Bottom if self.flip_rows => direction.opposite(),
+                Direction::Left | Direction::Right if self.flip_cols => direction.opposite(),
+                _ => *direction,
+            };
+            self.moves.push(PlannedMove { direction, goal });
+        }
+    }
+}
+
+///Cell next to `cell` in `direction` on board of width `n`
+fn step(n: usize, cell: usize, direction: Direction) -> Option<usize> {
+    let (r, c) = (cell / n, cell % n);
+    match direction {
+        Direction::Top if r > 0 => Some(cell - n),
+        Direction::Bottom if r + 1 < n => Some(cell + n),
+        Direction::Left if c > 0 => Some(cell - 1),
+        Direction::Right if c + 1 < n => Some(cell + 1),
+        _ => None,
+    }
+}
+
+///Shortest moves of zero field inside `region` after which `done` is true
+///for cells of zero field and of `tiles`. Other tiles may go anywhere in the region
+fn search<F: Fn(usize, &[usize]) -> bool>(n: usize, cells: &[u16], region: &[bool], tiles: &[u16], done: F) -> Option<Vec<Direction>> {
+    //States are cells of zero field and tiles, numbered in the region only
+    let region_cells: Vec<usize> = (0..cells.len()).filter(|i| region[*i]).collect();
+    let mut number = vec![usize::MAX; cells.len()];
+    for (i, cell) in region_cells.iter().enumerate() {
+        number[*cell] = i;
+    }
+    let m = region_cells.len();
+    let start: Vec<usize> = std::iter::once((n * n) as u16).chain(tiles.iter().copied())
+        .map(|tile| cells.iter().position(|v| *v == tile).map_or(usize::MAX, |cell| number[cell]))
+        .collect();
+    if start.contains(&usize::MAX) {
+        return None;
+    }
+    let encode = |state: &[usize]| state.iter().rev().fold(0, |code, i| code * m + i);
+    let decode = |mut code: usize| (0..=tiles.len()).map(|_| { let i = code % m; code /= m; i }).collect::<Vec<usize>>();
+    let mut parent = vec![usize::MAX; m.pow(tiles.len() as u32 + 1)];
+    let start = encode(&start);
+    parent[start] = start;
+    let mut queue = VecDeque::from(vec![start]);
+    while let Some(code) = queue.pop_front() {
+        let state = decode(code);
+        let at: Vec<usize> = state.iter().map(|i| region_cells[*i]).collect();
+        if done(at[0], &at[1..]) {
+            let mut moves = vec![];
+            let mut code = code;
+            while parent[code] != code {
+                let (from, to) = (region_cells[decode(parent[code])[0]], region_cells[decode(code)[0]]);
+                moves.extend(Direction::ALL.iter().copied().find(|d| step(n, from, *d) == Some(to)));
+                code = parent[code];
+            }
+            moves.reverse();
+            return Some(moves);
+        }
+        for direction in Direction::ALL.iter() {
+            let next = match step(n, at[0], *direction) {
+                Some(next) if region[next] => number[next],
+                _ => continue
+            };
+            let mut moved = state.clone();
+            if let Some(tile) = moved[1..].iter().position(|i| *i == next) {
+                moved[tile + 1] = state[0];
+            }
+            moved[0] = next;
+            let moved = encode(&moved);
+            if parent[moved] == usize::MAX {
+                parent[moved] = code;
+                queue.push_back(moved);
+            }
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::goal::GoalLayout;
+
+    fn check(board: &Gameboard) -> Vec<PlannedMove> {
+        let moves = solve(board).unwrap();
+        let mut solved = board.clone();
+        for m in &moves {
+            assert!(solved.move_zero(m.direction), "{}", board);
+        }
+        assert!(solved.is_over(), "{}", board);
+        moves
+    }
+
+    #[test]
+    fn strategy_test() {
+        for seed in 0..20 {
+            for size in 2..=6 {
+                for goal in &[GoalLayout::Standard, GoalLayout::BlankFirst, GoalLayout::Snake] {
+                    check(&Gameboard::generate(seed, size, *goal, Default::default()));
+                }
+            }
+        }
+        let board = Gameboard::generate(1, 10, GoalLayout::Standard, Default::default());
+        let moves = check(&board);
+        assert_eq!(moves.last().unwrap().goal, SubGoal::Square);
+
+        let board: Gameboard = "1 2 3/4 5 6/7 0 8".parse().unwrap();
+        assert_eq!(check(&board), vec![PlannedMove { direction: Direction::Right, goal: SubGoal::Square }]);
+        let board: Gameboard = "2 1 3/4 5 6/7 8 0".parse().unwrap();
+        assert_eq!(solve(&board), Err(StrategyError::Unsolvable));
+        let board = Gameboard::solved(3, GoalLayout::Spiral);
+        assert_eq!(solve(&board), Err(StrategyError::Layout));
+        assert_eq!(solve(&"1 2 0 0".parse().unwrap()), Err(StrategyError::Unsupported));
+        //Swapped tiles of odd torus need one move across the edge
+        let board = Gameboard { topology: Topology::Torus, ..Gameboard::generate(2, 5, GoalLayout::Snake, Default::default()) };
+        let mut board = board;
+        board.cells.swap(0, 1);
+        let moves = check(&board);
+        assert!(moves.iter().any(|m| m.goal == SubGoal::Across));
+    }
+
+    #[test]
+    fn sub_goal_test() {
+        let board: Gameboard = "2 0 3/1 4 5/7 8 6".parse().unwrap();
+        let moves = check(&board);
+        assert_eq!(moves[0].goal, SubGoal::Place { tile: 1, line: Line::Row(0) });
+        assert_eq!(moves[0].goal.to_string(), "row 1: place 1");
+        assert_eq!(SubGoal::Pair { tiles: [9, 13], line: Line::Column(0) }.to_string(), "column 1: 9 and 13 together");
+        //Blank first layout is solved from the bottom
+        let board = Gameboard { goal: GoalLayout::BlankFirst, ..board };
+        let board = Gameboard { cells: vec![9, 1, 2, 3, 4, 5, 8, 6, 7], ..board };
+        let moves = check(&board);
+        assert!(matches!(moves[0].goal, SubGoal::Place { line: Line::Row(2), .. }));
+    }
+}
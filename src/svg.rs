@@ -0,0 +1,188 @@
+//! SVG pictures of boards and solutions for docs, colors are taken from view settings.
+//! Moves are moves of the zero field, as in replays and solver output.
+
+use std::fmt::Write;
+
+use graphics::types::Color;
+
+use crate::animator::Direction;
+use crate::gameboard::{Gameboard, Position, OBSTACLE};
+use crate::gameboard_view::GameboardViewSettings;
+
+///Side of a cell in pixels
+const CELL: f64 = 40.0;
+///Space around boards and for titles above them
+const MARGIN: f64 = 10.0;
+const TITLE: f64 = 24.0;
+///Frames in one row of a strip
+const STRIP_COLUMNS: usize = 5;
+
+///Move `index` of the solution can't be done
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct IllegalMove(pub usize);
+
+///`rgb(...)` with opacity attribute of `name`
+fn paint(name: &str, color: Color) -> String {
+    let [r, g, b] = [0, 1, 2].map(|i| (color[i].clamp(0.0, 1.0) * 255.0).round() as u8);
+    if color[3] < 1.0 {
+        format!("{0}=\"rgb({1},{2},{3})\" {0}-opacity=\"{4}\"", name, r, g, b, color[3])
+    } else {
+        format!("{}=\"rgb({},{},{})\"", name, r, g, b)
+    }
+}
+
+fn header(width: f64, height: f64, settings: &GameboardViewSettings) -> String {
+    format!("<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{0}\" height=\"{1}\" viewBox=\"0 0 {0} {1}\" \
+        font-family=\"sans-serif\" text-anchor=\"middle\" dominant-baseline=\"central\">\n\
+        <defs><marker id=\"arrow\" viewBox=\"0 0 10 10\" refX=\"8\" refY=\"5\" markerWidth=\"5\" markerHeight=\"5\" orient=\"auto\">\
+        <path d=\"M0,0 L10,5 L0,10 z\" {2}/></marker></defs>\n\
+        <rect width=\"100%\" height=\"100%\" {3}/>\n",
+        width, height, paint("fill", settings.border_color), paint("fill", settings.zero_color))
+}
+
+///Board with top left corner at `x`, `y`, `highlight` tile has color of selection
+fn draw_board(out: &mut String, board: &Gameboard, x: f64, y: f64, settings: &GameboardViewSettings, highlight: Option<Position>) {
+    let side = CELL * board.size as f64;
+    let font = CELL * 0.45;
+    let _ = writeln!(out, "<g transform=\"translate({},{})\">", x, y);
+    let _ = writeln!(out, "<rect width=\"{0}\" height=\"{0}\" {1} {2} stroke-width=\"2\"/>",
+        side, paint("fill", settings.zero_color), paint("stroke", settings.border_color));
+    for (i, value) in board.cells.iter().enumerate() {
+        if *value == board.blank() {
+            continue;
+        }
+        let cell = Position::from_index(i, board.size);
+        if *value == OBSTACLE {
+            let _ = writeln!(out, "<rect x=\"{}\" y=\"{}\" width=\"{2}\" height=\"{2}\" {3}/>",
+                cell.col as f64 * CELL, cell.row as f64 * CELL, CELL, paint("fill", settings.obstacle_color));
+            continue;
+        }
+        let fill = if Some(cell) == highlight { settings.selected_color } else { settings.background_color };
+        let _ = writeln!(out, "<g transform=\"translate({},{})\"><rect width=\"{2}\" height=\"{2}\" {3} {4}/>\
+            <text x=\"{5}\" y=\"{5}\" font-size=\"{6}\" {7}>{8}</text></g>",
+            cell.col as f64 * CELL, cell.row as f64 * CELL, CELL,
+            paint("fill", fill), paint("stroke", settings.between_color),
+            CELL / 2.0, font, paint("fill", settings.text_color), value);
+    }
+    out.push_str("</g>\n");
+}
+
+///One figure of `board`
+pub fn board(board: &Gameboard, settings: &GameboardViewSettings) -> String {
+    let side = CELL * board.size as f64;
+    let mut out = header(side + 2.0 * MARGIN, side + 2.0 * MARGIN, settings);
+    draw_board(&mut out, board, MARGIN, MARGIN, settings, None);
+    out.push_str("</svg>\n");
+    out
+}
+
+///Boards after every move of `moves` in rows of frames. Every frame has move number
+///and an arrow of the moved tile
+pub fn strip(board: &Gameboard, moves: &[Direction], settings: &GameboardViewSettings) -> Result<String, IllegalMove> {
+    let side = CELL * board.size as f64;
+    let (frame_width, frame_height) = (side + 2.0 * MARGIN, side + TITLE + MARGIN);
+    let frames = moves.len() + 1;
+    let columns = frames.min(STRIP_COLUMNS);
+    let rows = frames.div_ceil(columns);
+    let mut out = header(frame_width * columns as f64, frame_height * rows as f64 + MARGIN, settings);
+    let mut board = board.clone();
+    for frame in 0..frames {
+        let (x, y) = ((frame % columns) as f64 * frame_width + MARGIN, (frame / columns) as f64 * frame_height + TITLE);
+        let mut arrow = None;
+        if frame > 0 {
+            let zero = board.zero();
+            if !board.move_zero(moves[frame - 1]) {
+                return Err(IllegalMove(frame - 1));
+            }
+            //Tile goes from the new zero field to the old one
+            arrow = Some((board.zero(), zero));
+        }
+        let title = if frame == 0 { "start".to_owned() } else { format!("{}", frame) };
+        let _ = writeln!(out, "<text x=\"{}\" y=\"{}\" font-size=\"16\" {}>{}</text>",
+            x + side / 2.0, y - TITLE / 2.0, paint("fill", settings.text_color), title);
+        draw_board(&mut out, &board, x, y, settings, arrow.map(|(_, to)| to));
+        if let Some((from, to)) = arrow {
+            let center = |p: Position| [x + (p.col as f64 + 0.5) * CELL, y + (p.row as f64 + 0.5) * CELL];
+            let ([x1, y1], [x2, y2]) = (center(from), center(to));
+            let _ = writeln!(out, "<line x1=\"{}\" y1=\"{}\" x2=\"{}\" y2=\"{}\" {} stroke-width=\"3\" marker-end=\"url(#arrow)\"/>",
+                x1, y1, x2, y2, paint("stroke", settings.border_color));
+        }
+    }
+    out.push_str("</svg>\n");
+    Ok(out)
+}
+
+///Board whose tiles slide by `moves` with SMIL animation, `seconds` for every move, and number of the move
+pub fn animated(board: &Gameboard, moves: &[Direction], settings: &GameboardViewSettings, seconds: f64) -> Result<String, IllegalMove> {
+    let side = CELL * board.size as f64;
+    //Place of every value after every move
+    let mut places = vec![vec![]; board.cells.len() + 1];
+    let mut current = board.clone();
+    let mut record = |b: &Gameboard| for (i, v) in b.cells.iter().enumerate() {
+        places[*v as usize].push(Position::from_index(i, b.size));
+    };
+    record(&current);
+    for (i, direction) in moves.iter().enumerate() {
+        if !current.move_zero(*direction) {
+            return Err(IllegalMove(i));
+        }
+        record(&current);
+    }
+    let duration = seconds * moves.len().max(1) as f64;
+    let key_times: Vec<String> = (0..=moves.len()).map(|i| format!("{}", i as f64 / moves.len().max(1) as f64)).collect();
+    let mut out = header(side + 2.0 * MARGIN, side + TITLE + MARGIN, settings);
+    //Static board with obstacles only, tiles are animated groups over it
+    let empty = Gameboard { cells: board.cells.iter().map(|v| if *v == OBSTACLE { OBSTACLE } else { board.blank() }).collect(), ..board.clone() };
+    draw_board(&mut out, &empty, MARGIN, TITLE, settings, None);
+    let tile = |v: usize| v != board.blank() as usize && v != OBSTACLE as usize;
+    for (value, path) in places.iter().enumerate().filter(|(v, p)| tile(*v) && !p.is_empty()) {
+        let values: Vec<String> = path.iter()
+            .map(|p| format!("{},{}", MARGIN + p.col as f64 * CELL, TITLE + p.row as f64 * CELL))
+            .collect();
+        //Viewers without SMIL show the first place
+        let _ = writeln!(out, "<g transform=\"translate({10})\"><animateTransform attributeName=\"transform\" type=\"translate\" \
+            values=\"{}\" keyTimes=\"{}\" dur=\"{}s\" fill=\"freeze\"/><rect width=\"{3}\" height=\"{3}\" {4} {5}/>\
+            <text x=\"{6}\" y=\"{6}\" font-size=\"{7}\" {8}>{9}</text></g>",
+            values.join(";"), key_times.join(";"), duration, CELL,
+            paint("fill", settings.background_color), paint("stroke", settings.between_color),
+            CELL / 2.0, CELL * 0.45, paint("fill", settings.text_color), value, values[0]);
+    }
+    for i in 0..=moves.len() {
+        let title = if i == 0 { "start".to_owned() } else { format!("move {}", i) };
+        //Every title is shown for its move, the last one stays
+        let end = if i == moves.len() { "fill=\"freeze\"".to_owned() } else { format!("dur=\"{}s\"", seconds) };
+        let _ = writeln!(out, "<text x=\"{}\" y=\"{}\" font-size=\"16\" {} opacity=\"0\">{}\
+            <set attributeName=\"opacity\" to=\"1\" begin=\"{}s\" {}/></text>",
+            MARGIN + side / 2.0, TITLE / 2.0, paint("fill", settings.text_color), title, i as f64 * seconds, end);
+    }
+    out.push_str("</svg>\n");
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gameboard_view::Theme;
+
+    #[test]
+    fn svg_test() {
+        let settings = GameboardViewSettings::with_theme(Theme::Dark);
+        let start: Gameboard = "1 2 3/4 5 6/0 7 8".parse().unwrap();
+        let figure = board(&start, &settings);
+        assert!(figure.starts_with("<svg "));
+        assert_eq!(figure.matches("<text").count(), 8);
+        assert!(figure.contains(&paint("fill", settings.background_color)));
+
+        let moves = [Direction::Right, Direction::Right];
+        let frames = strip(&start, &moves, &settings).unwrap();
+        assert_eq!(frames.matches("marker-end").count(), 2);
+        assert!(frames.contains(">start</text>") && frames.contains(">2</text>"));
+        assert_eq!(strip(&start, &[Direction::Left], &settings), Err(IllegalMove(0)));
+
+        let animation = animated(&start, &moves, &settings, 0.5).unwrap();
+        assert_eq!(animation.matches("<animateTransform").count(), 8);
+        //Tile 7 goes one cell left, then stays
+        assert!(animation.contains("values=\"50,104;10,104;10,104\""));
+        assert!(animation.contains(">move 2<set attributeName=\"opacity\" to=\"1\" begin=\"1s\" fill=\"freeze\"/>"));
+    }
+}
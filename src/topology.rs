@@ -0,0 +1,80 @@
+//! Topology of the board: which cells are next to each other.
+
+use crate::animator::Direction;
+use crate::gameboard::Position;
+
+#[derive(Debug, Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Hash, Default)]
+pub enum Topology {
+    ///Cells at the edges have no neighbours beyond them
+    #[default]
+    Flat,
+    ///Opposite edges are joined, zero field goes across them to the other side
+    Torus,
+}
+
+impl Topology {
+    pub const ALL: [Topology; 2] = [Topology::Flat, Topology::Torus];
+
+    ///Cell next to `cell` in `direction` on board with width `size`
+    pub fn step(self, cell: Position, direction: Direction, size: usize) -> Option<Position> {
+        let (row, col) = (cell.row, cell.col);
+        match (self, direction) {
+            (_, Direction::Top) if row > 0 => Some(Position::new(row - 1, col)),
+            (_, Direction::Right) if col + 1 < size => Some(Position::new(row, col + 1)),
+            (_, Direction::Bottom) if row + 1 < size => Some(Position::new(row + 1, col)),
+            (_, Direction::Left) if col > 0 => Some(Position::new(row, col - 1)),
+            (Topology::Torus, Direction::Top) => Some(Position::new(size - 1, col)),
+            (Topology::Torus, Direction::Right) => Some(Position::new(row, 0)),
+            (Topology::Torus, Direction::Bottom) => Some(Position::new(0, col)),
+            (Topology::Torus, Direction::Left) => Some(Position::new(row, size - 1)),
+            (Topology::Flat, _) => None,
+        }
+    }
+
+    ///Direction of the step from `from` to `to`, None if they aren't neighbours.
+    ///Cells of 2 wide board are neighbours both ways, the step inside the board is taken
+    pub fn direction(self, from: Position, to: Position, size: usize) -> Option<Direction> {
+        Direction::ALL.iter().copied().find(|d| Topology::Flat.step(from, *d, size) == Some(to))
+            .or_else(|| Direction::ALL.iter().copied().find(|d| from != to && self.step(from, *d, size) == Some(to)))
+    }
+
+    ///The step from `from` to `to` goes across an edge
+    pub fn is_wrap(self, from: Position, to: Position, size: usize) -> bool {
+        Topology::Flat.direction(from, to, size).is_none() && self.direction(from, to, size).is_some()
+    }
+
+    ///Fewest steps between `a` and `b` along one row or column of `size` cells
+    pub fn distance(self, a: usize, b: usize, size: usize) -> usize {
+        let distance = a.abs_diff(b);
+        match self {
+            Topology::Flat => distance,
+            Topology::Torus => distance.min(size - distance),
+        }
+    }
+}
+
+names!(Topology, "topology", {
+    Flat => "flat",
+    Torus => "torus",
+});
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn topology_test() {
+        let corner = Position::new(0, 3);
+        assert_eq!(Topology::Flat.step(corner, Direction::Right, 4), None);
+        assert_eq!(Topology::Torus.step(corner, Direction::Right, 4), Some(Position::new(0, 0)));
+        assert_eq!(Topology::Torus.step(corner, Direction::Top, 4), Some(Position::new(3, 3)));
+        assert_eq!(Topology::Torus.direction(corner, Position::new(0, 0), 4), Some(Direction::Right));
+        assert_eq!(Topology::Flat.direction(corner, Position::new(0, 0), 4), None);
+        assert!(Topology::Torus.is_wrap(corner, Position::new(3, 3), 4));
+        assert!(!Topology::Torus.is_wrap(Position::new(0, 0), Position::new(0, 1), 2));
+        assert_eq!(Topology::Torus.distance(0, 3, 4), 1);
+        assert_eq!(Topology::Flat.distance(0, 3, 4), 3);
+        assert_eq!("torus".parse::<Topology>(), Ok(Topology::Torus));
+        assert!("sphere".parse::<Topology>().is_err());
+    }
+}
@@ -0,0 +1,182 @@
+//! Lessons of the human method on 4x4 board: the first row, then the first column,
+//! then the 3x3 rest the same way. Every lesson is a scripted board and steps with tiles to place,
+//! tiles placed before can't be moved.
+
+use crate::gameboard::Gameboard;
+
+///Step of a lesson is done when all its tiles are on their places
+#[derive(Debug)]
+pub struct Step {
+    pub text: &'static str,
+    pub targets: &'static [u16],
+}
+
+#[derive(Debug)]
+pub struct Lesson {
+    pub title: &'static str,
+    pub board: &'static str,
+    ///Tiles placed by lessons before this one
+    pub placed: &'static [u16],
+    pub steps: &'static [Step],
+}
+
+pub const LESSONS: [Lesson; 3] = [
+    Lesson {
+        title: "First row",
+        board: "13 1 2 3/6 5 7 15/9 4 8 14/10 0 11 12",
+        placed: &[],
+        steps: &[
+            Step { text: "Put 1 and 2 on their places", targets: &[1, 2] },
+            Step { text: "4 where 3 goes, 3 under it, then slide both in", targets: &[3, 4] },
+        ],
+    },
+    Lesson {
+        title: "First column",
+        board: "1 2 3 4/13 5 6 15/10 8 11 12/9 0 7 14",
+        placed: &[1, 2, 3, 4],
+        steps: &[
+            Step { text: "Put 5 under 1", targets: &[5] },
+            Step { text: "13 where 9 goes, 9 right of it, then both in", targets: &[9, 13] },
+        ],
+    },
+    Lesson {
+        title: "The 3x3 rest",
+        board: "1 2 3 4/5 0 8 14/9 7 15 10/13 6 12 11",
+        placed: &[1, 2, 3, 4, 5, 9, 13],
+        steps: &[
+            Step { text: "The rest is a smaller puzzle, put 6 first", targets: &[6] },
+            Step { text: "7 and 8 go together, as 3 and 4 did", targets: &[7, 8] },
+            Step { text: "Now its column: 10 and 14 together", targets: &[10, 14] },
+            Step { text: "Go around the last square to finish", targets: &[11, 12, 15] },
+        ],
+    },
+];
+
+///What a move has changed in the tutorial
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Advance {
+    Stay,
+    Step,
+    ///Next lesson has begun, its board is to be played
+    Lesson,
+    Finished,
+}
+
+///Current step of the lessons
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Tutorial {
+    pub lesson: usize,
+    pub step: usize,
+    ///Why the last move wasn't allowed
+    pub feedback: Option<String>,
+}
+
+impl Tutorial {
+    pub fn new() -> Self {
+        Tutorial::default()
+    }
+
+    pub fn lesson(&self) -> &'static Lesson {
+        &LESSONS[self.lesson.min(LESSONS.len() - 1)]
+    }
+
+    pub fn step(&self) -> &'static Step {
+        let steps = self.lesson().steps;
+        &steps[self.step.min(steps.len() - 1)]
+    }
+
+    ///Scripted board of current lesson
+    pub fn board(&self) -> Gameboard {
+        self.lesson().board.parse().expect("lesson board")
+    }
+
+    ///Tiles of earlier lessons and steps, they stay on their places
+    pub fn locked(&self) -> Vec<u16> {
+        let lesson = self.lesson();
+        let mut locked = lesson.placed.to_vec();
+        for step in &lesson.steps[..self.step.min(lesson.steps.len())] {
+            locked.extend_from_slice(step.targets);
+        }
+        locked
+    }
+
+    ///`tile` may be moved now, else feedback tells why
+    pub fn allows(&mut self, tile: u16) -> bool {
+        if self.locked().contains(&tile) {
+            self.feedback = Some(format!("{} is in place already, go around it", tile));
+            false
+        } else {
+            self.feedback = None;
+            true
+        }
+    }
+
+    ///Go on to the next steps if tiles of current one are placed on `board`
+    pub fn update(&mut self, board: &Gameboard) -> Advance {
+        let placed = board.placed();
+        let is_placed = |tile: &u16| board.cells.iter().position(|v| v == tile).is_some_and(|i| placed[i]);
+        let mut advance = Advance::Stay;
+        while self.step < self.lesson().steps.len() && self.step().targets.iter().all(is_placed) {
+            self.step += 1;
+            advance = Advance::Step;
+        }
+        if self.step == self.lesson().steps.len() {
+            self.lesson += 1;
+            self.step = 0;
+            advance = if self.is_finished() { Advance::Finished } else { Advance::Lesson };
+        }
+        advance
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.lesson >= LESSONS.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::animator::Direction;
+
+    #[test]
+    fn lessons_test() {
+        for (i, lesson) in LESSONS.iter().enumerate() {
+            let board: Gameboard = lesson.board.parse().unwrap();
+            assert!(board.is_solvable(), "{}", lesson.title);
+            let placed = board.placed();
+            assert!(lesson.placed.iter().all(|tile| placed[*tile as usize - 1]), "{}", lesson.title);
+            //Every lesson places what the next one starts with
+            let all: Vec<u16> = lesson.placed.iter().chain(lesson.steps.iter().flat_map(|s| s.targets.iter())).copied().collect();
+            match LESSONS.get(i + 1) {
+                Some(next) => assert_eq!(next.placed, &all[..]),
+                None => assert_eq!(all.len(), 15),
+            }
+        }
+    }
+
+    #[test]
+    fn tutorial_test() {
+        let mut tutorial = Tutorial::new();
+        let mut board = tutorial.board();
+        assert!(tutorial.allows(13));
+        //Zero goes around to the corner, then 1 and 2 slide left
+        for direction in [Direction::Top, Direction::Top, Direction::Left, Direction::Top, Direction::Right] {
+            assert!(board.move_zero(direction));
+        }
+        assert_eq!(tutorial.update(&board), Advance::Stay);
+        assert!(board.move_zero(Direction::Right));
+        assert_eq!(tutorial.update(&board), Advance::Step);
+        assert_eq!(tutorial.step, 1);
+        assert!(!tutorial.allows(2));
+        assert_eq!(tutorial.feedback.as_deref(), Some("2 is in place already, go around it"));
+        assert!(tutorial.allows(3));
+        assert_eq!(tutorial.feedback, None);
+
+        let solved = Gameboard::solved(4, Default::default());
+        assert_eq!(tutorial.update(&solved), Advance::Lesson);
+        assert_eq!(tutorial.locked(), vec![1, 2, 3, 4]);
+        assert_eq!(tutorial.update(&solved), Advance::Lesson);
+        assert_eq!(tutorial.update(&solved), Advance::Finished);
+        assert!(tutorial.is_finished());
+    }
+}